@@ -2,6 +2,7 @@ use std::fmt::{Debug, Display, Formatter};
 use crate::{interpreter, scanner, sexp};
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     ScanError(scanner::ScanError),
     SexpParseError(sexp::ParseError),
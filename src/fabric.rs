@@ -0,0 +1,444 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Index;
+
+use crate::interpreter::FaceName;
+
+pub mod brick;
+pub mod growth;
+pub mod physics;
+pub mod shaping;
+pub mod stage;
+pub mod vector;
+pub mod vulcanize;
+
+pub use vector::Vector;
+
+/// Refers to a joint, interval or face of a [Fabric]. Handles stay valid when other parts are
+/// removed, and a handle to a removed part never comes to refer to anything else.
+pub struct Handle<T> {
+    index: usize,
+    part: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(index: usize) -> Handle<T> {
+        Handle { index, part: PhantomData }
+    }
+
+    /// The position of the part in creation order, counting removed parts.
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.index)
+    }
+}
+
+pub type JointId = Handle<Joint>;
+pub type IntervalId = Handle<Interval>;
+pub type FaceId = Handle<Face>;
+
+/// Parts in creation order. A removed part leaves an empty slot behind, so the slots of the
+/// others never move and their handles stay valid.
+#[derive(Debug, Clone)]
+struct Slots<T> {
+    slots: Vec<Option<T>>,
+    count: usize,
+}
+
+impl<T> Default for Slots<T> {
+    fn default() -> Self {
+        Slots { slots: Vec::new(), count: 0 }
+    }
+}
+
+impl<T> Slots<T> {
+    fn insert(&mut self, part: T) -> Handle<T> {
+        self.slots.push(Some(part));
+        self.count += 1;
+        Handle::new(self.slots.len() - 1)
+    }
+
+    fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let part = self.slots.get_mut(handle.index)?.take()?;
+        self.count -= 1;
+        Some(part)
+    }
+
+    fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slots.get(handle.index)?.as_ref()
+    }
+
+    fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.slots.get_mut(handle.index)?.as_mut()
+    }
+
+    fn iter(&self) -> impl Iterator<Item=(Handle<T>, &T)> + '_ {
+        self.slots.iter().enumerate()
+            .filter_map(|(index, slot)| Some((Handle::new(index), slot.as_ref()?)))
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item=(Handle<T>, &mut T)> + '_ {
+        self.slots.iter_mut().enumerate()
+            .filter_map(|(index, slot)| Some((Handle::new(index), slot.as_mut()?)))
+    }
+}
+
+impl<T> Index<Handle<T>> for Slots<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &T {
+        self.get(handle).unwrap_or_else(|| panic!("{handle:?} has been removed"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Joint {
+    pub position: Vector,
+    pub velocity: Vector,
+    /// Kilograms.
+    pub mass: f64,
+}
+
+impl Joint {
+    pub fn new(position: Vector, mass: f64) -> Joint {
+        Joint { position, velocity: Vector::ZERO, mass }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Role {
+    /// A compression member, pushing its joints apart.
+    Push,
+    /// A tension member, pulling its joints together.
+    Pull,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub alpha: JointId,
+    pub omega: JointId,
+    pub role: Role,
+    /// The length in meters at which the interval exerts no force.
+    pub ideal_length: f64,
+    pub stiffness: f64,
+}
+
+impl Interval {
+    pub fn joints(&self) -> [JointId; 2] {
+        [self.alpha, self.omega]
+    }
+
+    pub fn touches(&self, joint: JointId) -> bool {
+        self.alpha == joint || self.omega == joint
+    }
+
+    /// The joint at the other end from the given one.
+    pub fn other_joint(&self, joint: JointId) -> JointId {
+        if self.alpha == joint { self.omega } else { self.alpha }
+    }
+}
+
+/// A triangle of joints on the outside of a brick, which growth and shaping work from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Face {
+    pub joints: [JointId; 3],
+    pub face_name: FaceName,
+    pub mark: Option<String>,
+}
+
+/// The tensegrity itself: joints, the intervals between them, and the faces of its bricks.
+///
+/// ```
+/// use tenscript::fabric::{Fabric, Interval, Joint, Role, Vector};
+///
+/// let mut fabric = Fabric::new();
+/// let [a, b, c] = [0.0, 1.0, 2.0].map(|x| fabric.create_joint(Joint::new(Vector::new(x, 0.0, 0.0), 1.0)));
+/// let push = fabric.create_interval(Interval { alpha: a, omega: c, role: Role::Push, ideal_length: 2.0, stiffness: 1.0 });
+/// let pull = fabric.create_interval(Interval { alpha: a, omega: b, role: Role::Pull, ideal_length: 1.0, stiffness: 1.0 });
+/// fabric.remove_joint(b);
+/// assert!(fabric.interval(pull).is_none());
+/// assert_eq!(fabric.length(push), 2.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Fabric {
+    joints: Slots<Joint>,
+    intervals: Slots<Interval>,
+    faces: Slots<Face>,
+}
+
+impl Fabric {
+    pub fn new() -> Fabric {
+        Fabric::default()
+    }
+
+    pub fn create_joint(&mut self, joint: Joint) -> JointId {
+        self.joints.insert(joint)
+    }
+
+    /// Adds an interval between two joints.
+    ///
+    /// # Panics
+    ///
+    /// If either joint has been removed, or if both ends are the same joint.
+    pub fn create_interval(&mut self, interval: Interval) -> IntervalId {
+        assert_ne!(interval.alpha, interval.omega, "interval from a joint to itself");
+        for joint in interval.joints() {
+            assert!(self.joints.get(joint).is_some(), "interval to removed joint {joint:?}");
+        }
+        self.intervals.insert(interval)
+    }
+
+    /// Adds a face on three joints.
+    ///
+    /// # Panics
+    ///
+    /// If any of the joints has been removed.
+    pub fn create_face(&mut self, face: Face) -> FaceId {
+        for joint in face.joints {
+            assert!(self.joints.get(joint).is_some(), "face on removed joint {joint:?}");
+        }
+        self.faces.insert(face)
+    }
+
+    /// Removes a joint along with every interval and face that uses it.
+    pub fn remove_joint(&mut self, id: JointId) -> Option<Joint> {
+        let joint = self.joints.remove(id)?;
+        let intervals: Vec<_> = self.intervals_at(id).map(|(interval, _)| interval).collect();
+        for interval in intervals {
+            self.intervals.remove(interval);
+        }
+        let faces: Vec<_> = self.faces()
+            .filter(|(_, face)| face.joints.contains(&id))
+            .map(|(face, _)| face)
+            .collect();
+        for face in faces {
+            self.faces.remove(face);
+        }
+        Some(joint)
+    }
+
+    /// Merges one joint into another, which moves halfway to it. Intervals and faces of the
+    /// merged joint are moved onto the kept one, except intervals that would then join the
+    /// kept joint to itself or duplicate an interval it already has.
+    ///
+    /// # Panics
+    ///
+    /// If either joint has been removed.
+    pub fn join_joints(&mut self, keep: JointId, merged: JointId) {
+        let gone = self.joints.remove(merged).unwrap_or_else(|| panic!("{merged:?} has been removed"));
+        let kept = self.joints.get_mut(keep).unwrap_or_else(|| panic!("{keep:?} has been removed"));
+        kept.position = (kept.position + gone.position) / 2.0;
+        kept.velocity = (kept.velocity + gone.velocity) / 2.0;
+        let moved: Vec<_> = self.intervals_at(merged).map(|(interval, _)| interval).collect();
+        for id in moved {
+            let other = self.intervals[id].other_joint(merged);
+            if other == keep || self.interval_between(keep, other).is_some() {
+                self.intervals.remove(id);
+                continue;
+            }
+            let interval = self.intervals.get_mut(id).expect("interval at the joint");
+            for end in [&mut interval.alpha, &mut interval.omega] {
+                if *end == merged {
+                    *end = keep;
+                }
+            }
+        }
+        for (_, face) in self.faces.iter_mut() {
+            for joint in &mut face.joints {
+                if *joint == merged {
+                    *joint = keep;
+                }
+            }
+        }
+    }
+
+    pub fn remove_interval(&mut self, id: IntervalId) -> Option<Interval> {
+        self.intervals.remove(id)
+    }
+
+    /// Removes a face, leaving its joints and intervals in place.
+    pub fn remove_face(&mut self, id: FaceId) -> Option<Face> {
+        self.faces.remove(id)
+    }
+
+    pub fn joint(&self, id: JointId) -> Option<&Joint> {
+        self.joints.get(id)
+    }
+
+    pub fn joint_mut(&mut self, id: JointId) -> Option<&mut Joint> {
+        self.joints.get_mut(id)
+    }
+
+    pub fn interval(&self, id: IntervalId) -> Option<&Interval> {
+        self.intervals.get(id)
+    }
+
+    pub fn interval_mut(&mut self, id: IntervalId) -> Option<&mut Interval> {
+        self.intervals.get_mut(id)
+    }
+
+    pub fn face(&self, id: FaceId) -> Option<&Face> {
+        self.faces.get(id)
+    }
+
+    pub fn face_mut(&mut self, id: FaceId) -> Option<&mut Face> {
+        self.faces.get_mut(id)
+    }
+
+    pub fn joints(&self) -> impl Iterator<Item=(JointId, &Joint)> + '_ {
+        self.joints.iter()
+    }
+
+    pub fn joints_mut(&mut self) -> impl Iterator<Item=(JointId, &mut Joint)> + '_ {
+        self.joints.iter_mut()
+    }
+
+    pub fn intervals(&self) -> impl Iterator<Item=(IntervalId, &Interval)> + '_ {
+        self.intervals.iter()
+    }
+
+    pub fn intervals_mut(&mut self) -> impl Iterator<Item=(IntervalId, &mut Interval)> + '_ {
+        self.intervals.iter_mut()
+    }
+
+    pub fn faces(&self) -> impl Iterator<Item=(FaceId, &Face)> + '_ {
+        self.faces.iter()
+    }
+
+    pub fn joint_count(&self) -> usize {
+        self.joints.count
+    }
+
+    pub fn interval_count(&self) -> usize {
+        self.intervals.count
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.count
+    }
+
+    /// The intervals with an end at the given joint.
+    pub fn intervals_at(&self, joint: JointId) -> impl Iterator<Item=(IntervalId, &Interval)> + '_ {
+        self.intervals().filter(move |(_, interval)| interval.touches(joint))
+    }
+
+    pub fn interval_between(&self, alpha: JointId, omega: JointId) -> Option<IntervalId> {
+        self.intervals_at(alpha)
+            .find(|(_, interval)| interval.other_joint(alpha) == omega)
+            .map(|(id, _)| id)
+    }
+
+    /// The faces carrying the given mark.
+    pub fn faces_marked<'a>(&'a self, mark: &'a str) -> impl Iterator<Item=(FaceId, &'a Face)> + 'a {
+        self.faces().filter(move |(_, face)| face.mark.as_deref() == Some(mark))
+    }
+
+    pub fn position(&self, joint: JointId) -> Vector {
+        self.joints[joint].position
+    }
+
+    /// The current distance between the ends of an interval.
+    pub fn length(&self, interval: IntervalId) -> f64 {
+        let interval = &self.intervals[interval];
+        self.position(interval.alpha).distance(self.position(interval.omega))
+    }
+
+    /// How far an interval is from its ideal length, as a fraction of it. Positive when stretched.
+    pub fn strain(&self, interval: IntervalId) -> f64 {
+        let ideal_length = self.intervals[interval].ideal_length;
+        (self.length(interval) - ideal_length) / ideal_length
+    }
+
+    pub fn face_centroid(&self, face: FaceId) -> Vector {
+        self.faces[face].joints.iter().map(|&joint| self.position(joint)).sum::<Vector>() / 3.0
+    }
+
+    /// The unit normal of a face, by the right hand rule over its joints in order.
+    pub fn face_normal(&self, face: FaceId) -> Vector {
+        let [a, b, c] = self.faces[face].joints.map(|joint| self.position(joint));
+        (b - a).cross(c - a).normalized()
+    }
+
+    /// The average position of all joints.
+    pub fn centroid(&self) -> Vector {
+        if self.joint_count() == 0 {
+            return Vector::ZERO;
+        }
+        self.joints().map(|(_, joint)| joint.position).sum::<Vector>() / self.joint_count() as f64
+    }
+
+    /// The lowest and highest joint heights, if there are any joints.
+    pub fn altitude_range(&self) -> Option<(f64, f64)> {
+        self.joints().map(|(_, joint)| joint.position.y).fold(None, |range, y| match range {
+            None => Some((y, y)),
+            Some((low, high)) => Some((low.min(y), high.max(y))),
+        })
+    }
+}
+
+/// Indexing panics if the part has been removed. Use [Fabric::joint] and friends when
+/// that is possible.
+impl Index<JointId> for Fabric {
+    type Output = Joint;
+
+    fn index(&self, id: JointId) -> &Joint {
+        &self.joints[id]
+    }
+}
+
+impl Index<IntervalId> for Fabric {
+    type Output = Interval;
+
+    fn index(&self, id: IntervalId) -> &Interval {
+        &self.intervals[id]
+    }
+}
+
+impl Index<FaceId> for Fabric {
+    type Output = Face;
+
+    fn index(&self, id: FaceId) -> &Face {
+        &self.faces[id]
+    }
+}
@@ -0,0 +1,372 @@
+use crate::fabric::{Face, FaceId, Fabric, Interval, IntervalId, Joint, JointId, Role, Vector};
+use crate::interpreter::{FabricPlan, FaceName, SeedType};
+use crate::quantity::Length;
+
+const PHI: f64 = 1.618_033_988_749_895;
+
+/// The length of a push in the canonical brick, whose joints are the vertices of an
+/// icosahedron with edges of length two.
+const CANONICAL_PUSH_LENGTH: f64 = 2.0 * PHI;
+
+pub const JOINT_MASS: f64 = 1.0;
+pub const PUSH_STIFFNESS: f64 = 1.0;
+pub const PULL_STIFFNESS: f64 = 1.0;
+
+/// The faces of the canonical brick by octant. A face is named `+` when an even number of
+/// its signs are negative, and its opposite is in the opposite octant.
+const OCTANTS: [(FaceName, [f64; 3]); 8] = [
+    (FaceName::Aplus, [1.0, 1.0, 1.0]),
+    (FaceName::Bplus, [1.0, -1.0, -1.0]),
+    (FaceName::Cplus, [-1.0, 1.0, -1.0]),
+    (FaceName::Dplus, [-1.0, -1.0, 1.0]),
+    (FaceName::Aminus, [-1.0, -1.0, -1.0]),
+    (FaceName::Bminus, [-1.0, 1.0, 1.0]),
+    (FaceName::Cminus, [1.0, -1.0, 1.0]),
+    (FaceName::Dminus, [1.0, 1.0, -1.0]),
+];
+
+/// A joint of the canonical left-handed brick: the cyclic permutation `axis` of
+/// `(0, first, second * PHI)`. Each push joins the two joints that differ only in `second`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Vertex {
+    axis: usize,
+    first: bool,
+    second: bool,
+}
+
+impl Vertex {
+    fn all() -> impl Iterator<Item=Vertex> {
+        (0..12).map(Vertex::from_index)
+    }
+
+    fn from_index(index: usize) -> Vertex {
+        Vertex { axis: index / 4, first: index & 2 == 0, second: index & 1 == 0 }
+    }
+
+    fn index(self) -> usize {
+        self.axis * 4 + usize::from(!self.first) * 2 + usize::from(!self.second)
+    }
+
+    fn sign(positive: bool) -> f64 {
+        if positive { 1.0 } else { -1.0 }
+    }
+
+    fn position(self) -> Vector {
+        let [a, b, c] = [0.0, Vertex::sign(self.first), Vertex::sign(self.second) * PHI];
+        match self.axis {
+            0 => Vector::new(a, b, c),
+            1 => Vector::new(c, a, b),
+            _ => Vector::new(b, c, a),
+        }
+    }
+
+    fn push_partner(self) -> Vertex {
+        Vertex { second: !self.second, ..self }
+    }
+
+    /// The three joints of the face in the octant with the given signs.
+    fn octant_face([x, y, z]: [f64; 3]) -> [Vertex; 3] {
+        let positive = |sign: f64| sign > 0.0;
+        [
+            Vertex { axis: 0, first: positive(y), second: positive(z) },
+            Vertex { axis: 1, first: positive(z), second: positive(x) },
+            Vertex { axis: 2, first: positive(x), second: positive(y) },
+        ]
+    }
+}
+
+fn octant(face_name: FaceName) -> [f64; 3] {
+    OCTANTS.iter()
+        .find(|(name, _)| *name == face_name)
+        .map(|(_, signs)| *signs)
+        .unwrap_or_else(|| panic!("{face_name} is not a face of a brick"))
+}
+
+/// The centroid of a face of the canonical brick. It points straight out of the face.
+fn canonical_face_centroid(face_name: FaceName) -> Vector {
+    Vertex::octant_face(octant(face_name)).iter().map(|vertex| vertex.position()).sum::<Vector>() / 3.0
+}
+
+/// An affine map, used to place a copy of the canonical brick.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    matrix: [[f64; 3]; 3],
+    translation: Vector,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        translation: Vector::ZERO,
+    };
+
+    pub fn scale(factor: f64) -> Transform {
+        let mut matrix = Transform::IDENTITY.matrix;
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = factor;
+        }
+        Transform { matrix, translation: Vector::ZERO }
+    }
+
+    pub fn translation(translation: Vector) -> Transform {
+        Transform { translation, ..Transform::IDENTITY }
+    }
+
+    /// Reflection through the plane at `offset` along the unit `normal`.
+    pub fn reflection(normal: Vector, offset: f64) -> Transform {
+        let n = [normal.x, normal.y, normal.z];
+        let mut matrix = Transform::IDENTITY.matrix;
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell -= 2.0 * n[i] * n[j];
+            }
+        }
+        Transform { matrix, translation: normal * (2.0 * offset) }
+    }
+
+    /// A half turn about the line through the origin along the unit `axis`.
+    pub fn half_turn(axis: Vector) -> Transform {
+        let n = [axis.x, axis.y, axis.z];
+        let mut matrix = [[0.0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = 2.0 * n[i] * n[j] - if i == j { 1.0 } else { 0.0 };
+            }
+        }
+        Transform { matrix, translation: Vector::ZERO }
+    }
+
+    pub fn apply(&self, point: Vector) -> Vector {
+        let [x, y, z] = self.matrix.map(|[a, b, c]| a * point.x + b * point.y + c * point.z);
+        Vector::new(x, y, z) + self.translation
+    }
+
+    /// This transform followed by the outer one.
+    pub fn then(&self, outer: &Transform) -> Transform {
+        let mut matrix = [[0.0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| outer.matrix[i][k] * self.matrix[k][j]).sum();
+            }
+        }
+        Transform { matrix, translation: outer.apply(self.translation) }
+    }
+
+    /// Negative when the transform mirrors, turning a left-handed brick right-handed.
+    pub fn determinant(&self) -> f64 {
+        let [[a, b, c], [d, e, f], [g, h, i]] = self.matrix;
+        a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Chirality {
+    Left,
+    Right,
+}
+
+/// One brick of a fabric: six pushes, whose twelve joints are joined by twenty four pulls,
+/// which make up its eight faces.
+#[derive(Debug, Clone)]
+pub struct Brick {
+    transform: Transform,
+    joints: [JointId; 12],
+    pushes: [IntervalId; 6],
+    faces: [Option<FaceId>; 8],
+}
+
+impl Brick {
+    /// Places the canonical brick in the world.
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn chirality(&self) -> Chirality {
+        if self.transform.determinant() < 0.0 { Chirality::Right } else { Chirality::Left }
+    }
+
+    pub fn joints(&self) -> &[JointId; 12] {
+        &self.joints
+    }
+
+    pub fn pushes(&self) -> &[IntervalId; 6] {
+        &self.pushes
+    }
+
+    /// The fabric face for one of this brick's own faces, unless the face is shared with a
+    /// neighbouring brick and so no longer on the outside.
+    pub fn face(&self, face_name: FaceName) -> Option<FaceId> {
+        let index = OCTANTS.iter().position(|(name, _)| *name == face_name)?;
+        self.faces[index]
+    }
+
+    /// Forgets one of the brick's faces, which has been covered by a neighbour.
+    pub(super) fn take_face(&mut self, face_name: FaceName) -> Option<FaceId> {
+        let index = OCTANTS.iter().position(|(name, _)| *name == face_name)?;
+        self.faces[index].take()
+    }
+
+    pub fn faces(&self) -> impl Iterator<Item=(FaceName, FaceId)> + '_ {
+        OCTANTS.iter().zip(self.faces)
+            .filter_map(|((face_name, _), face)| Some((*face_name, face?)))
+    }
+
+    /// The world position of the centre of the brick.
+    pub fn center(&self) -> Vector {
+        self.transform.apply(Vector::ZERO)
+    }
+
+    /// The placement of a brick grown out of the given face, attached by its opposite face
+    /// and with the same chirality.
+    pub fn grown_transform(&self, face_name: FaceName) -> Transform {
+        let centroid = canonical_face_centroid(face_name);
+        Transform::half_turn(centroid.normalized())
+            .then(&Transform::translation(centroid * 2.0))
+            .then(&self.transform)
+    }
+
+    /// The placement of the mirror image of this brick in the plane of the given face.
+    pub fn mirrored_transform(&self, face_name: FaceName) -> Transform {
+        mirrored(&self.transform, face_name)
+    }
+}
+
+/// What to name the fabric face for each face of a new brick, if it is to get a face at all.
+pub type FaceLabels = [Option<FaceName>; 8];
+
+/// Every face labelled with its own name, apart from the one given.
+pub fn labels_without(omitted: FaceName) -> FaceLabels {
+    OCTANTS.map(|(face_name, _)| (face_name != omitted).then_some(face_name))
+}
+
+/// Adds a brick placed by the transform. Joints of the brick that coincide with joints of
+/// the base brick's face are shared with it, along with the pulls between them.
+pub fn create_brick(fabric: &mut Fabric, transform: Transform, base: Option<(&Brick, FaceName)>, labels: FaceLabels) -> Brick {
+    let mut joints = [None; 12];
+    let mut shared_face = None;
+    if let Some((base_brick, face_name)) = base {
+        let base_vertices = Vertex::octant_face(octant(face_name));
+        for base_vertex in base_vertices {
+            let position = fabric.position(base_brick.joints[base_vertex.index()]);
+            let vertex = Vertex::all()
+                .min_by(|a, b| {
+                    let distance = |vertex: &Vertex| transform.apply(vertex.position()).distance(position);
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap();
+            joints[vertex.index()] = Some(base_brick.joints[base_vertex.index()]);
+        }
+        shared_face = Some(base_vertices.map(|vertex| base_brick.joints[vertex.index()]));
+    }
+    let joints = Vertex::all()
+        .zip(joints)
+        .map(|(vertex, joint)| joint.unwrap_or_else(|| {
+            fabric.create_joint(Joint::new(transform.apply(vertex.position()), JOINT_MASS))
+        }))
+        .collect::<Vec<_>>();
+    let joints: [JointId; 12] = joints.try_into().unwrap();
+    let pushes = [0, 2, 4, 6, 8, 10].map(|index| {
+        let vertex = Vertex::from_index(index);
+        let (alpha, omega) = (joints[vertex.index()], joints[vertex.push_partner().index()]);
+        let ideal_length = fabric.position(alpha).distance(fabric.position(omega));
+        fabric.create_interval(Interval { alpha, omega, role: Role::Push, ideal_length, stiffness: PUSH_STIFFNESS })
+    });
+    let center = transform.apply(Vector::ZERO);
+    let mut faces = [None; 8];
+    for (((_, signs), label), face) in OCTANTS.iter().zip(labels).zip(&mut faces) {
+        let mut face_joints = Vertex::octant_face(*signs).map(|vertex| joints[vertex.index()]);
+        for i in 0..3 {
+            let (alpha, omega) = (face_joints[i], face_joints[(i + 1) % 3]);
+            let shared = shared_face.is_some_and(|shared: [JointId; 3]| shared.contains(&alpha) && shared.contains(&omega));
+            if !shared {
+                let ideal_length = fabric.position(alpha).distance(fabric.position(omega));
+                fabric.create_interval(Interval { alpha, omega, role: Role::Pull, ideal_length, stiffness: PULL_STIFFNESS });
+            }
+        }
+        let Some(face_name) = label else {
+            continue;
+        };
+        let [a, b, c] = face_joints.map(|joint| fabric.position(joint));
+        if (b - a).cross(c - a).dot((a + b + c) / 3.0 - center) < 0.0 {
+            face_joints.swap(1, 2);
+        }
+        *face = Some(fabric.create_face(Face { joints: face_joints, face_name, mark: None }));
+    }
+    Brick { transform, joints, pushes, faces }
+}
+
+/// The length of a push in the seed brick: the fabric scale, one meter unless given,
+/// times the build phase scale.
+pub fn push_length(plan: &FabricPlan) -> Length {
+    let scale = plan.scale().unwrap_or(Length::from_meters(1.0));
+    match plan.build_phase().scale() {
+        Some(percent) => scale.scaled(percent.ratio()),
+        None => scale,
+    }
+}
+
+/// Creates the seed a plan asks for, a left-handed brick unless it says otherwise.
+pub fn create_plan_seed(fabric: &mut Fabric, plan: &FabricPlan) -> Vec<Brick> {
+    let seed_type = plan.build_phase().seed().unwrap_or(SeedType::Left);
+    create_seed(fabric, seed_type, push_length(plan))
+}
+
+/// Creates the seed of a fabric, resting on the ground, and returns its bricks. A single
+/// seed is one brick with all eight faces. A double seed is a brick with its mirror image
+/// grown on its `A-` face: the first brick keeps its `+` faces, and the faces of the second
+/// that mirror those are named with `-`.
+///
+/// ```
+/// use tenscript::fabric::{Fabric, Role};
+/// use tenscript::fabric::brick::create_seed;
+/// use tenscript::quantity::Length;
+/// use tenscript::SeedType;
+///
+/// let mut fabric = Fabric::new();
+/// let bricks = create_seed(&mut fabric, SeedType::LeftRight, Length::from_meters(2.0));
+/// assert_eq!(bricks.len(), 2);
+/// assert_eq!((fabric.joint_count(), fabric.face_count()), (21, 8));
+/// let pushes: Vec<_> = fabric.intervals().filter(|(_, interval)| interval.role == Role::Push).collect();
+/// assert_eq!(pushes.len(), 12);
+/// assert!(pushes.iter().all(|(id, _)| (fabric.length(*id) - 2.0).abs() < 1e-9));
+/// assert!(fabric.joints().all(|(joint, _)| pushes.iter().any(|(_, push)| push.touches(joint))));
+/// let (bottom, _) = fabric.altitude_range().unwrap();
+/// assert!(bottom.abs() < 1e-9);
+/// for (id, _) in fabric.faces() {
+///     assert!(fabric.face_normal(id).dot(fabric.face_centroid(id) - fabric.centroid()) > 0.0);
+/// }
+/// ```
+pub fn create_seed(fabric: &mut Fabric, seed_type: SeedType, push_length: Length) -> Vec<Brick> {
+    let scale = Transform::scale(push_length.meters() / CANONICAL_PUSH_LENGTH);
+    let (first, double) = match seed_type {
+        SeedType::Left => (scale, None),
+        SeedType::Right => (mirror_x().then(&scale), None),
+        SeedType::LeftRight => (scale, Some(FaceName::Aminus)),
+        SeedType::RightLeft => (mirror_x().then(&scale), Some(FaceName::Aminus)),
+    };
+    let lowest = |transform: &Transform| Vertex::all()
+        .map(|vertex| transform.apply(vertex.position()).y)
+        .fold(f64::INFINITY, f64::min);
+    let Some(shared) = double else {
+        let transform = first.then(&Transform::translation(Vector::UP * -lowest(&first)));
+        let brick = create_brick(fabric, transform, None, OCTANTS.map(|(face_name, _)| Some(face_name)));
+        return vec![brick];
+    };
+    let second = mirrored(&first, shared);
+    let lift = Transform::translation(Vector::UP * -lowest(&first).min(lowest(&second)));
+    let plus = |face_name: FaceName| matches!(face_name, FaceName::Aplus | FaceName::Bplus | FaceName::Cplus | FaceName::Dplus);
+    let first_brick = create_brick(fabric, first.then(&lift), None, OCTANTS.map(|(face_name, _)| plus(face_name).then_some(face_name)));
+    let second_brick = create_brick(fabric, second.then(&lift), Some((&first_brick, shared)),
+                                    OCTANTS.map(|(face_name, _)| plus(face_name).then_some(face_name.opposite())));
+    vec![first_brick, second_brick]
+}
+
+fn mirrored(transform: &Transform, face_name: FaceName) -> Transform {
+    let centroid = canonical_face_centroid(face_name);
+    Transform::reflection(centroid.normalized(), centroid.magnitude()).then(transform)
+}
+
+/// Mirrors through the plane `x = 0`, turning the canonical left-handed brick right-handed.
+fn mirror_x() -> Transform {
+    Transform::reflection(Vector::new(1.0, 0.0, 0.0), 0.0)
+}
@@ -0,0 +1,187 @@
+use std::fmt::{Display, Formatter};
+
+use crate::fabric::Fabric;
+use crate::fabric::brick::{Brick, create_brick, create_plan_seed, labels_without};
+use crate::interpreter::{FabricPlan, FaceName, NodePath, TenscriptNode};
+
+/// Where a brick came from in the plan.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Origin {
+    Seed,
+    /// The brick was grown by the grow at the path, and is the `step`th counting from one.
+    Grow { path: NodePath, step: usize },
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Seed => f.write_str("seed"),
+            Origin::Grow { path, step } => write!(f, "{path} brick {step}"),
+        }
+    }
+}
+
+/// The origin of every brick of a build, in the order the bricks were created.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildLog {
+    origins: Vec<Origin>,
+}
+
+impl BuildLog {
+    /// The origin of the brick at the index in [Build::bricks].
+    pub fn origin(&self, brick: usize) -> Option<&Origin> {
+        self.origins.get(brick)
+    }
+
+    pub fn origins(&self) -> impl Iterator<Item=(usize, &Origin)> + '_ {
+        self.origins.iter().enumerate()
+    }
+
+    /// The bricks grown by the grow at the path.
+    pub fn bricks_of<'a>(&'a self, path: &'a NodePath) -> impl Iterator<Item=usize> + 'a {
+        self.origins()
+            .filter(move |(_, origin)| matches!(origin, Origin::Grow { path: grown, .. } if grown == path))
+            .map(|(brick, _)| brick)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// The face to grow from or mark is not on the outside of the bricks it should be on,
+    /// either because it is the seed face or because something was already grown from it.
+    FaceNotFound { face: FaceName },
+}
+
+#[derive(Debug, Clone)]
+pub struct GrowthError {
+    kind: ErrorKind,
+    path: NodePath,
+}
+
+impl GrowthError {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// The grow that could not be carried out.
+    pub fn path(&self) -> &NodePath {
+        &self.path
+    }
+}
+
+impl Display for GrowthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let GrowthError { kind, path } = self;
+        write!(f, "{kind:?} in {path}")
+    }
+}
+
+impl std::error::Error for GrowthError {}
+
+/// A fabric grown from a plan, with the bricks it was built from.
+#[derive(Debug, Clone)]
+pub struct Build {
+    fabric: Fabric,
+    bricks: Vec<Brick>,
+    log: BuildLog,
+}
+
+impl Build {
+    pub fn fabric(&self) -> &Fabric {
+        &self.fabric
+    }
+
+    pub fn fabric_mut(&mut self) -> &mut Fabric {
+        &mut self.fabric
+    }
+
+    pub fn bricks(&self) -> &[Brick] {
+        &self.bricks
+    }
+
+    pub fn log(&self) -> &BuildLog {
+        &self.log
+    }
+
+    pub fn into_fabric(self) -> Fabric {
+        self.fabric
+    }
+}
+
+/// Builds the seed of a plan and carries out its growth. Each grow attaches its bricks in a
+/// line out of the named face, marks faces of the last of them, and then grows its branch
+/// from that last brick. A face that is grown from is no longer on the outside, so a mark on
+/// it is lost.
+///
+/// ```
+/// use tenscript::fabric::growth::{Origin, grow};
+///
+/// let plan = tenscript::interpret(include_str!("../../example.ss")).unwrap();
+/// let build = grow(&plan).unwrap();
+/// assert_eq!(build.bricks().len(), 10);
+/// assert_eq!(build.fabric().joint_count(), 12 + 9 * 9);
+/// assert_eq!(build.fabric().intervals().count(), 6 * 10 + 24 + 21 * 9);
+/// assert_eq!(build.log().origin(0), Some(&Origin::Seed));
+/// assert_eq!(build.log().origin(9).unwrap().to_string(), "grow B- brick 3");
+/// assert_eq!(build.fabric().faces_marked("arm").count(), 1);
+/// ```
+pub fn grow(plan: &FabricPlan) -> Result<Build, GrowthError> {
+    let mut fabric = Fabric::new();
+    let bricks = create_plan_seed(&mut fabric, plan);
+    let log = BuildLog { origins: bricks.iter().map(|_| Origin::Seed).collect() };
+    let mut build = Build { fabric, bricks, log };
+    if let Some(growth) = plan.build_phase().growth() {
+        let seed = (0..build.bricks.len()).collect::<Vec<_>>();
+        build.grow_node(growth, &NodePath::default(), &seed)?;
+    }
+    Ok(build)
+}
+
+impl Build {
+    /// Grows the node out of the faces of the given bricks.
+    fn grow_node(&mut self, node: &TenscriptNode, parent: &NodePath, base: &[usize]) -> Result<(), GrowthError> {
+        match node {
+            TenscriptNode::Branch { subtrees } => {
+                for subtree in subtrees {
+                    self.grow_node(subtree, parent, base)?;
+                }
+            }
+            TenscriptNode::Grow { face, forward_count, branch, marks } => {
+                let path = parent.child(*face);
+                let error = |face| GrowthError { kind: ErrorKind::FaceNotFound { face }, path: path.clone() };
+                let (mut tip, brick_face) = self.find_face(base, *face).ok_or_else(|| error(*face))?;
+                for step in 1..=*forward_count {
+                    if let Some(covered) = self.bricks[tip].take_face(brick_face) {
+                        self.fabric.remove_face(covered);
+                    }
+                    let parent_brick = &self.bricks[tip];
+                    let transform = parent_brick.grown_transform(brick_face);
+                    let labels = labels_without(brick_face.opposite());
+                    let brick = create_brick(&mut self.fabric, transform, Some((parent_brick, brick_face)), labels);
+                    self.bricks.push(brick);
+                    self.log.origins.push(Origin::Grow { path: path.clone(), step });
+                    tip = self.bricks.len() - 1;
+                }
+                let tips = if *forward_count == 0 { base.to_vec() } else { vec![tip] };
+                for mark in marks {
+                    let (brick, brick_face) = self.find_face(&tips, mark.face()).ok_or_else(|| error(mark.face()))?;
+                    let face = self.bricks[brick].face(brick_face).expect("found face");
+                    self.fabric.face_mut(face).expect("face of a brick").mark = Some(mark.name().to_string());
+                }
+                if let Some(branch) = branch {
+                    self.grow_node(branch, &path, &tips)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The brick with an outside face of the given name, and the brick's own name for it.
+    fn find_face(&self, bricks: &[usize], face_name: FaceName) -> Option<(usize, FaceName)> {
+        bricks.iter().find_map(|&brick| {
+            self.bricks[brick].faces()
+                .find(|(_, face)| self.fabric.face(*face).is_some_and(|face| face.face_name == face_name))
+                .map(|(brick_face, _)| (brick, brick_face))
+        })
+    }
+}
@@ -0,0 +1,339 @@
+use crate::fabric::{Fabric, IntervalId, Role, Vector};
+use crate::interpreter::{FabricPlan, Features, Surface, SurfaceCharacter};
+use crate::quantity::{Duration, ITERATIONS_PER_SECOND, Percent};
+
+/// Seconds of simulated time in one iteration.
+const TIME_STEP: f64 = 1.0 / ITERATIONS_PER_SECOND;
+
+/// Newtons exerted by an interval of stiffness one at a strain of one, before the
+/// stiffness factor.
+const ELASTIC_FORCE: f64 = 10_000.0;
+
+/// Meters per second squared at a gravity of 100%.
+const GRAVITY: f64 = 9.81;
+
+/// The fraction of its velocity a joint loses in one iteration at a drag of 100%.
+const DRAG: f64 = 0.005;
+
+/// How far pushes are lengthened when pretensing at a pretenst factor of 100%, as a ratio
+/// of their length.
+const PRETENST: f64 = 0.03;
+
+/// Newtons per meter of depth pushing a joint back up out of the ground at an antigravity
+/// of 100%.
+const ANTIGRAVITY: f64 = 100_000.0;
+
+/// How joints meet the ground.
+///
+/// ```
+/// use tenscript::fabric::Fabric;
+/// use tenscript::fabric::brick::create_plan_seed;
+/// use tenscript::fabric::physics::{Ground, Physics, Simulation};
+///
+/// let plan = tenscript::interpret("(fabric (surface (friction 40%) (restitution 20%) (height -25cm)) (build (seed :left)))").unwrap();
+/// let physics = Physics::from_plan(&plan);
+/// assert_eq!(physics.ground, Ground::Contact { friction: 0.4, restitution: 0.2, height: -0.25 });
+/// let mut fabric = Fabric::new();
+/// create_plan_seed(&mut fabric, &plan);
+/// let mut simulation = Simulation::new(fabric, physics);
+/// simulation.run_frames(40);
+/// let (bottom, _) = simulation.fabric().altitude_range().unwrap();
+/// assert!((bottom + 0.25).abs() < 0.01);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Ground {
+    /// A joint that reaches the ground stays where it touched it.
+    Frozen { height: f64 },
+    /// The ground pushes joints below it back up with the antigravity, but with only the
+    /// `restitution` fraction of that push while they rise, so they bounce back that much.
+    /// Joints below it are held back sideways by the `friction` fraction of that push, the
+    /// way a block sliding on a floor is, until they stop.
+    Contact { friction: f64, restitution: f64, height: f64 },
+}
+
+impl Ground {
+    /// The ground for a plan's surface. Without one, the ground pushes back fully and has
+    /// no friction.
+    pub fn new(surface: Option<Surface>) -> Ground {
+        match surface {
+            None => Ground::Contact { friction: 0.0, restitution: 1.0, height: 0.0 },
+            Some(Surface::Character(SurfaceCharacter::Frozen)) => Ground::Frozen { height: 0.0 },
+            Some(Surface::Character(SurfaceCharacter::Sticky)) => Ground::Contact { friction: 1.0, restitution: 0.0, height: 0.0 },
+            Some(Surface::Character(SurfaceCharacter::Bouncy)) => Ground::Contact { friction: 0.1, restitution: 0.8, height: 0.0 },
+            Some(Surface::Tuned { friction, restitution, height }) => Ground::Contact {
+                friction: friction.ratio().value(),
+                restitution: restitution.ratio().value(),
+                height: height.meters(),
+            },
+        }
+    }
+
+    pub fn height(&self) -> f64 {
+        match *self {
+            Ground::Frozen { height } | Ground::Contact { height, .. } => height,
+        }
+    }
+}
+
+/// The features that drive the simulation, as plain numbers with defaults filled in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Physics {
+    pub iterations_per_frame: usize,
+    pub gravity: f64,
+    pub drag: f64,
+    pub stiffness: f64,
+    pub push_over_pull: f64,
+    pub antigravity: f64,
+    pub ground: Ground,
+    /// How far pushes are lengthened when pretensing, as a ratio of their length.
+    pub pretenst: f64,
+    /// Iterations taken for an interval to ease to a new ideal length.
+    pub interval_countdown: f64,
+    /// Iterations taken to pretense the fabric.
+    pub pretensing_countdown: f64,
+    /// How far pushes are lengthened while shaping, as a ratio of their length.
+    pub shaping_pretenst: f64,
+    pub shaping_drag: f64,
+    pub shaping_stiffness: f64,
+}
+
+impl Physics {
+    pub fn new(features: &Features) -> Physics {
+        let features = Features::overlay(Features::defaults(), features.clone());
+        let ratio = |percent: Option<Percent>| percent.expect("defaults set every feature").ratio().value();
+        let countdown = |duration: Option<Duration>| duration.expect("defaults set every feature").iterations();
+        Physics {
+            iterations_per_frame: features.iterations_per_frame().expect("defaults set every feature") as usize,
+            gravity: ratio(features.gravity()),
+            drag: ratio(features.drag()),
+            stiffness: ratio(features.stiffness_factor()),
+            push_over_pull: ratio(features.push_over_pull()),
+            antigravity: ratio(features.antigravity()),
+            ground: Ground::new(None),
+            pretenst: ratio(features.pretenst_factor()) * PRETENST,
+            interval_countdown: countdown(features.interval_countdown()),
+            pretensing_countdown: countdown(features.pretensing_countdown()),
+            shaping_pretenst: ratio(features.shaping_pretenst_factor()) * PRETENST,
+            shaping_drag: ratio(features.shaping_drag()),
+            shaping_stiffness: ratio(features.shaping_stiffness_factor()),
+        }
+    }
+
+    /// The same physics with the drag and stiffness used while shaping.
+    pub fn shaping(&self) -> Physics {
+        Physics { drag: self.shaping_drag, stiffness: self.shaping_stiffness, ..self.clone() }
+    }
+
+    /// The physics of a plan's features, standing on its surface.
+    pub fn from_plan(plan: &FabricPlan) -> Physics {
+        Physics { ground: Ground::new(plan.surface()), ..Physics::new(plan.features()) }
+    }
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics::new(&Features::default())
+    }
+}
+
+/// Settles a fabric by dynamic relaxation: every iteration, each joint is moved by the
+/// forces of its intervals, gravity and the ground, and slowed by drag. Nothing is drawn,
+/// so fabrics can be settled anywhere.
+///
+/// ```
+/// use tenscript::fabric::Fabric;
+/// use tenscript::fabric::brick::create_seed;
+/// use tenscript::fabric::physics::{Physics, Simulation};
+/// use tenscript::quantity::Length;
+/// use tenscript::SeedType;
+///
+/// let mut fabric = Fabric::new();
+/// create_seed(&mut fabric, SeedType::Left, Length::from_meters(1.0));
+/// let mut simulation = Simulation::new(fabric, Physics::default());
+/// simulation.run_frames(20);
+/// assert_eq!(simulation.iterations(), 2000);
+/// let (bottom, top) = simulation.fabric().altitude_range().unwrap();
+/// assert!(bottom > -0.01 && top > 0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    fabric: Fabric,
+    physics: Physics,
+    iterations: u64,
+    forces: Vec<Vector>,
+    length_changes: Vec<LengthChange>,
+}
+
+/// An interval easing from one ideal length to another.
+#[derive(Debug, Clone)]
+struct LengthChange {
+    interval: IntervalId,
+    from: f64,
+    to: f64,
+    elapsed: f64,
+    countdown: f64,
+}
+
+impl Simulation {
+    pub fn new(fabric: Fabric, physics: Physics) -> Simulation {
+        Simulation { fabric, physics, iterations: 0, forces: Vec::new(), length_changes: Vec::new() }
+    }
+
+    pub fn fabric(&self) -> &Fabric {
+        &self.fabric
+    }
+
+    pub fn fabric_mut(&mut self) -> &mut Fabric {
+        &mut self.fabric
+    }
+
+    pub fn into_fabric(self) -> Fabric {
+        self.fabric
+    }
+
+    pub fn physics(&self) -> &Physics {
+        &self.physics
+    }
+
+    pub fn physics_mut(&mut self) -> &mut Physics {
+        &mut self.physics
+    }
+
+    /// Iterations run so far.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// The speed of the fastest joint, in meters per second, which falls towards zero as
+    /// the fabric settles.
+    pub fn max_speed(&self) -> f64 {
+        self.fabric.joints()
+            .map(|(_, joint)| joint.velocity.magnitude())
+            .fold(0.0, f64::max)
+    }
+
+    /// Eases the ideal length of an interval to the given length over the interval countdown.
+    pub fn change_length(&mut self, interval: IntervalId, length: f64) {
+        self.change_length_over(interval, length, self.physics.interval_countdown);
+    }
+
+    /// Eases the ideal length of an interval to the given length over the given number of
+    /// iterations, starting slowly and finishing slowly. Any change already under way for
+    /// the interval is replaced, starting from wherever it had got to.
+    pub fn change_length_over(&mut self, interval: IntervalId, length: f64, countdown: f64) {
+        self.length_changes.retain(|change| change.interval != interval);
+        let Some(current) = self.fabric.interval(interval).map(|interval| interval.ideal_length) else {
+            return;
+        };
+        self.length_changes.push(LengthChange { interval, from: current, to: length, elapsed: 0.0, countdown });
+    }
+
+    /// Whether any interval is still easing to a new length.
+    pub fn is_changing_length(&self) -> bool {
+        !self.length_changes.is_empty()
+    }
+
+    /// Runs the given number of iterations.
+    pub fn step(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.iterate();
+        }
+    }
+
+    /// Runs the iterations of the given number of frames.
+    pub fn run_frames(&mut self, frames: usize) {
+        self.step(frames * self.physics.iterations_per_frame);
+    }
+
+    fn iterate(&mut self) {
+        self.ease_lengths();
+        let Physics { gravity, drag, stiffness: stiffness_factor, push_over_pull, antigravity, ground, .. } = self.physics;
+        let slots = self.fabric.joints().map(|(id, _)| id.index() + 1).max().unwrap_or(0);
+        self.forces.clear();
+        self.forces.resize(slots, Vector::ZERO);
+        for (id, interval) in self.fabric.intervals() {
+            let strain = self.fabric.strain(id);
+            let stiffness = match interval.role {
+                Role::Push => interval.stiffness * push_over_pull,
+                Role::Pull if strain < 0.0 => continue,
+                Role::Pull => interval.stiffness,
+            };
+            let (alpha, omega) = (self.fabric.position(interval.alpha), self.fabric.position(interval.omega));
+            let force = (omega - alpha).normalized() * (strain * stiffness * stiffness_factor * ELASTIC_FORCE);
+            self.forces[interval.alpha.index()] += force;
+            self.forces[interval.omega.index()] -= force;
+        }
+        for (id, joint) in self.fabric.joints_mut() {
+            let depth = ground.height() - joint.position.y;
+            if let Ground::Frozen { height } = ground {
+                if depth >= 0.0 {
+                    joint.position.y = height;
+                    joint.velocity = Vector::ZERO;
+                    continue;
+                }
+            }
+            let mut force = self.forces[id.index()];
+            force -= Vector::UP * (GRAVITY * gravity * joint.mass);
+            if let Ground::Contact { restitution, .. } = ground {
+                if depth > 0.0 {
+                    let rebound = if joint.velocity.y > 0.0 { restitution } else { 1.0 };
+                    force += Vector::UP * (depth * ANTIGRAVITY * antigravity * rebound);
+                }
+            }
+            joint.velocity += force * (TIME_STEP / joint.mass);
+            joint.velocity = joint.velocity * (1.0 - DRAG * drag).max(0.0);
+            if let Ground::Contact { friction, .. } = ground {
+                let sideways = Vector::new(joint.velocity.x, 0.0, joint.velocity.z);
+                let speed = sideways.magnitude();
+                if depth > 0.0 && speed > 0.0 {
+                    let slowing = friction * depth * ANTIGRAVITY * antigravity * TIME_STEP / joint.mass;
+                    joint.velocity -= sideways * (slowing.min(speed) / speed);
+                }
+            }
+            joint.position += joint.velocity * TIME_STEP;
+        }
+        self.iterations += 1;
+    }
+
+    fn ease_lengths(&mut self) {
+        let fabric = &mut self.fabric;
+        self.length_changes.retain_mut(|change| {
+            let Some(interval) = fabric.interval_mut(change.interval) else {
+                return false;
+            };
+            change.elapsed += 1.0;
+            let progress = if change.countdown > 0.0 { (change.elapsed / change.countdown).min(1.0) } else { 1.0 };
+            let eased = progress * progress * (3.0 - 2.0 * progress);
+            interval.ideal_length = change.from + (change.to - change.from) * eased;
+            progress < 1.0
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fabric::{Fabric, Joint, Vector};
+    use crate::fabric::physics::{Ground, Physics, Simulation};
+    use crate::quantity::ITERATIONS_PER_SECOND;
+
+    /// How far a lone joint resting on the ground slides in a second from 1 m/s.
+    fn slide(friction: f64) -> f64 {
+        let mut fabric = Fabric::new();
+        let joint = fabric.create_joint(Joint::new(Vector::ZERO, 1.0));
+        let ground = Ground::Contact { friction, restitution: 0.0, height: 0.0 };
+        let mut simulation = Simulation::new(fabric, Physics { ground, drag: 0.0, ..Physics::default() });
+        simulation.step(ITERATIONS_PER_SECOND as usize);
+        simulation.fabric_mut().joint_mut(joint).unwrap().velocity = Vector::new(1.0, 0.0, 0.0);
+        simulation.step(ITERATIONS_PER_SECOND as usize);
+        simulation.fabric().position(joint).x
+    }
+
+    #[test]
+    fn friction_sets_how_far_joints_slide() {
+        let (sticky, tuned, bouncy, slick) = (slide(1.0), slide(0.4), slide(0.1), slide(0.0));
+        assert!((0.01..0.1).contains(&sticky), "{sticky}");
+        assert!((tuned / sticky - 2.5).abs() < 0.3, "{tuned} against {sticky}");
+        assert!((bouncy / sticky - 10.0).abs() < 1.0, "{bouncy} against {sticky}");
+        assert!((slick - 1.0).abs() < 1e-6, "{slick}");
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+
+use crate::fabric::{Fabric, FaceId, Interval, IntervalId, JointId, Role};
+use crate::fabric::brick::PULL_STIFFNESS;
+use crate::fabric::physics::Simulation;
+
+/// What a shaping pull eases to, as a fraction of the distance it started at.
+const SHAPING_PULL_TARGET: f64 = 0.1;
+
+/// How close, as a fraction of the distance they started at, the joints of two faces must
+/// come before the faces are joined.
+const MEETING_DISTANCE: f64 = 0.2;
+
+/// Two faces with the same mark being drawn together by temporary pulls, one between each
+/// pair of joints that will be merged. Joints the faces already share need no pull.
+#[derive(Debug, Clone)]
+pub struct Join {
+    mark: String,
+    faces: [FaceId; 2],
+    pulls: Vec<(IntervalId, f64)>,
+}
+
+impl Join {
+    pub fn mark(&self) -> &str {
+        &self.mark
+    }
+
+    pub fn faces(&self) -> [FaceId; 2] {
+        self.faces
+    }
+}
+
+/// A marked face that shaping left as it was.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unjoined {
+    /// The face was left over after pairing the faces with its mark.
+    Unpaired { mark: String, face: FaceId },
+    /// The faces were still apart when shaping gave up on them.
+    Abandoned { mark: String, faces: [FaceId; 2] },
+}
+
+/// Starts drawing together each face with the next face carrying the same mark, in the
+/// order they were created. The pulls ease in over the interval countdown. A face left
+/// over when an odd number of faces carry a mark is returned as [Unjoined::Unpaired].
+///
+/// ```
+/// use tenscript::fabric::Fabric;
+/// use tenscript::fabric::growth::grow;
+/// use tenscript::fabric::physics::{Physics, Simulation};
+/// use tenscript::fabric::shaping::{start_shaping, Unjoined};
+///
+/// let source = "(fabric (build (grow A+ 1 (mark A+ :x) (mark B- :x) (mark C- :x))))";
+/// let fabric = grow(&tenscript::interpret(source).unwrap()).unwrap().into_fabric();
+/// let mut simulation = Simulation::new(fabric, Physics::default());
+/// let (joins, unjoined) = start_shaping(&mut simulation);
+/// assert_eq!(joins.len(), 1);
+/// let [Unjoined::Unpaired { mark, face }] = &unjoined[..] else { panic!("{unjoined:?}") };
+/// assert_eq!((mark.as_str(), joins[0].faces().contains(face)), ("x", false));
+/// ```
+pub fn start_shaping(simulation: &mut Simulation) -> (Vec<Join>, Vec<Unjoined>) {
+    let mut marked: BTreeMap<String, Vec<FaceId>> = BTreeMap::new();
+    for (id, face) in simulation.fabric().faces() {
+        if let Some(mark) = &face.mark {
+            marked.entry(mark.clone()).or_default().push(id);
+        }
+    }
+    let mut joins = Vec::new();
+    let mut unjoined = Vec::new();
+    for (mark, faces) in marked {
+        let pairs = faces.chunks_exact(2);
+        if let [face] = pairs.remainder() {
+            unjoined.push(Unjoined::Unpaired { mark: mark.clone(), face: *face });
+        }
+        for pair in pairs {
+            let faces = [pair[0], pair[1]];
+            let fabric = simulation.fabric_mut();
+            let pulls: Vec<_> = joint_pairs(fabric, faces).into_iter()
+                .map(|(alpha, omega)| {
+                    let distance = fabric.position(alpha).distance(fabric.position(omega));
+                    let pull = fabric.create_interval(Interval { alpha, omega, role: Role::Pull, ideal_length: distance, stiffness: PULL_STIFFNESS });
+                    (pull, distance)
+                })
+                .collect();
+            for &(pull, distance) in &pulls {
+                simulation.change_length(pull, distance * SHAPING_PULL_TARGET);
+            }
+            joins.push(Join { mark: mark.clone(), faces, pulls });
+        }
+    }
+    (joins, unjoined)
+}
+
+/// Joins the faces that have met, and returns how many joins are still under way. Joined
+/// faces become a ring of three joints inside the fabric: their pulls are shared, the two
+/// faces are removed, and so are the shaping pulls.
+///
+/// ```
+/// use tenscript::fabric::stage::{Stage, StageMachine};
+///
+/// let source = "(fabric (build (branch (grow A+ 1 (mark A+ :tip)) (grow B+ 1 (mark A+ :tip)))))";
+/// let mut machine = StageMachine::new(&tenscript::interpret(source).unwrap()).unwrap();
+/// let fabric = machine.simulation().fabric();
+/// let before = (fabric.joint_count(), fabric.interval_count(), fabric.face_count());
+/// while machine.stage() <= Stage::Shaping {
+///     machine.run_frames(1);
+/// }
+/// let fabric = machine.simulation().fabric();
+/// assert_eq!(fabric.faces_marked("tip").count(), 0);
+/// assert_eq!((fabric.joint_count(), fabric.interval_count(), fabric.face_count()),
+///            (before.0 - 3, before.1 - 3, before.2 - 2));
+/// ```
+pub fn continue_shaping(simulation: &mut Simulation, joins: &mut Vec<Join>) -> usize {
+    let fabric = simulation.fabric_mut();
+    joins.retain(|join| {
+        let met = join.pulls.iter()
+            .all(|&(pull, distance)| fabric.interval(pull).is_none() || fabric.length(pull) < distance * MEETING_DISTANCE);
+        if met {
+            complete(fabric, join);
+        }
+        !met
+    });
+    joins.len()
+}
+
+/// Gives up on the joins still under way, removing their shaping pulls, and returns them as
+/// [Unjoined::Abandoned].
+pub fn abandon_shaping(simulation: &mut Simulation, joins: Vec<Join>) -> Vec<Unjoined> {
+    joins.into_iter()
+        .map(|Join { mark, faces, pulls }| {
+            for (pull, _) in pulls {
+                simulation.fabric_mut().remove_interval(pull);
+            }
+            Unjoined::Abandoned { mark, faces }
+        })
+        .collect()
+}
+
+fn complete(fabric: &mut Fabric, join: &Join) {
+    let pairs: Vec<_> = join.pulls.iter()
+        .filter_map(|&(pull, _)| fabric.remove_interval(pull))
+        .map(|pull| (pull.alpha, pull.omega))
+        .collect();
+    for face in join.faces {
+        fabric.remove_face(face);
+    }
+    for (keep, merged) in pairs {
+        fabric.join_joints(keep, merged);
+    }
+}
+
+/// Pairs each joint of the first face with a joint of the second so that the pairs are as
+/// close together as they can be, and returns the pairs that are not already one joint. A
+/// joint the faces share is always paired with itself.
+fn joint_pairs(fabric: &Fabric, [a, b]: [FaceId; 2]) -> Vec<(JointId, JointId)> {
+    let (a, b) = (fabric[a].joints, fabric[b].joints);
+    const ORDERS: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+    let keeps_shared = |order: &&[usize; 3]| (0..3).all(|i| !b.contains(&a[i]) || b[order[i]] == a[i]);
+    let total = |order: &[usize; 3]| (0..3)
+        .map(|i| fabric.position(a[i]).distance(fabric.position(b[order[i]])))
+        .sum::<f64>();
+    let order = ORDERS.iter()
+        .filter(keeps_shared)
+        .min_by(|x, y| total(x).total_cmp(&total(y)))
+        .expect("an order pairing shared joints with themselves");
+    (0..3)
+        .map(|i| (a[i], b[order[i]]))
+        .filter(|(alpha, omega)| alpha != omega)
+        .collect()
+}
@@ -0,0 +1,228 @@
+use std::fmt::{Display, Formatter};
+
+use crate::fabric::growth::{grow, GrowthError};
+use crate::fabric::physics::{Physics, Simulation};
+use crate::fabric::{IntervalId, Role};
+use crate::fabric::shaping::{abandon_shaping, continue_shaping, start_shaping, Join, Unjoined};
+use crate::fabric::vulcanize::vulcanize;
+use crate::interpreter::FabricPlan;
+
+/// Where a fabric is in its life, from growth to standing pretensed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Stage {
+    /// Grown and vulcanized, and settling for the interval countdown.
+    Growing,
+    /// Drawing together faces with the same mark, with the shaping drag and stiffness and
+    /// the pushes lengthened by the shaping pretenst factor, until they meet and are joined.
+    Shaping,
+    /// Resting without pretension for the interval countdown.
+    Slack,
+    /// Lengthening the pushes by the pretenst factor over the pretensing countdown.
+    Pretensing,
+    /// Done, and simulated for as long as the caller likes.
+    Pretenst,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Stage::Growing => "growing",
+            Stage::Shaping => "shaping",
+            Stage::Slack => "slack",
+            Stage::Pretensing => "pretensing",
+            Stage::Pretenst => "pretenst",
+        })
+    }
+}
+
+/// A move from one stage to the next.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StageChange {
+    pub from: Stage,
+    pub to: Stage,
+    /// The iteration of the simulation at which the new stage began.
+    pub iteration: u64,
+}
+
+impl Display for StageChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let StageChange { from, to, iteration } = self;
+        write!(f, "{from} → {to} at {iteration}it")
+    }
+}
+
+/// Carries a plan through every stage, moving on as each countdown runs out.
+///
+/// ```
+/// use tenscript::fabric::Role;
+/// use tenscript::fabric::stage::{Stage, StageMachine};
+///
+/// let source = "(fabric (features (interval-countdown 500it) (pretensing-countdown 1000it)) (build (grow A+ 1)))";
+/// let plan = tenscript::interpret(source).unwrap();
+/// let mut machine = StageMachine::new(&plan).unwrap();
+/// assert_eq!(machine.stage(), Stage::Growing);
+/// let push_length = |machine: &StageMachine| machine.simulation().fabric().intervals()
+///     .find(|(_, interval)| interval.role == Role::Push)
+///     .map(|(_, interval)| interval.ideal_length)
+///     .unwrap();
+/// let slack = push_length(&machine);
+/// machine.step(3000);
+/// assert_eq!(machine.stage(), Stage::Pretenst);
+/// let stages: Vec<_> = machine.take_events().iter().map(|change| change.to).collect();
+/// assert_eq!(stages, [Stage::Shaping, Stage::Slack, Stage::Pretensing, Stage::Pretenst]);
+/// assert!(machine.take_events().is_empty());
+/// assert!((push_length(&machine) / slack - 1.03).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StageMachine {
+    simulation: Simulation,
+    stage: Stage,
+    /// Iterations left before the current stage ends by itself.
+    countdown: f64,
+    events: Vec<StageChange>,
+    /// The physics outside of shaping.
+    physics: Physics,
+    joins: Vec<Join>,
+    unjoined: Vec<Unjoined>,
+    /// Push lengths from before shaping, to go back to afterwards.
+    slack_lengths: Vec<(IntervalId, f64)>,
+}
+
+/// How many interval countdowns shaping may take before the faces that have not met are
+/// given up on.
+const SHAPING_COUNTDOWNS: f64 = 10.0;
+
+impl StageMachine {
+    /// Grows and vulcanizes the plan, and starts it settling.
+    pub fn new(plan: &FabricPlan) -> Result<StageMachine, GrowthError> {
+        let mut build = grow(plan)?;
+        if let Some(vulcanize_type) = plan.build_phase().vulcanize() {
+            vulcanize(&mut build, vulcanize_type);
+        }
+        let physics = Physics::from_plan(plan);
+        let countdown = physics.interval_countdown;
+        let simulation = Simulation::new(build.into_fabric(), physics.clone());
+        Ok(StageMachine {
+            simulation,
+            stage: Stage::Growing,
+            countdown,
+            events: Vec::new(),
+            physics,
+            joins: Vec::new(),
+            unjoined: Vec::new(),
+            slack_lengths: Vec::new(),
+        })
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn simulation(&self) -> &Simulation {
+        &self.simulation
+    }
+
+    pub fn simulation_mut(&mut self) -> &mut Simulation {
+        &mut self.simulation
+    }
+
+    /// The marked faces that shaping could not join, known once shaping is over.
+    ///
+    /// ```
+    /// use tenscript::fabric::stage::{Stage, StageMachine};
+    ///
+    /// let source = "(fabric (build (grow A+ 1 (mark A+ :x) (mark B- :x) (mark C+ :y))))";
+    /// let mut machine = StageMachine::new(&tenscript::interpret(source).unwrap()).unwrap();
+    /// while machine.stage() <= Stage::Shaping {
+    ///     machine.run_frames(1);
+    /// }
+    /// assert_eq!(machine.unjoined().len(), 1);
+    /// machine.run_frames(10);
+    /// assert!(machine.simulation().fabric().joints().all(|(_, joint)| joint.position.magnitude().is_finite()));
+    /// ```
+    pub fn unjoined(&self) -> &[Unjoined] {
+        &self.unjoined
+    }
+
+    /// The stage changes since the last time they were taken, oldest first.
+    pub fn take_events(&mut self) -> Vec<StageChange> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Runs the given number of iterations, changing stage along the way as needed.
+    pub fn step(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.simulation.step(1);
+            self.countdown -= 1.0;
+            if self.stage == Stage::Shaping {
+                continue_shaping(&mut self.simulation, &mut self.joins);
+            }
+            self.advance();
+        }
+    }
+
+    /// Runs the iterations of the given number of frames.
+    pub fn run_frames(&mut self, frames: usize) {
+        self.step(frames * self.simulation.physics().iterations_per_frame);
+    }
+
+    /// Moves on through every stage that is finished.
+    fn advance(&mut self) {
+        loop {
+            let next = match self.stage {
+                Stage::Growing if self.countdown <= 0.0 => Stage::Shaping,
+                Stage::Shaping if self.joins.is_empty() || self.countdown <= 0.0 => Stage::Slack,
+                Stage::Slack if self.countdown <= 0.0 => Stage::Pretensing,
+                Stage::Pretensing if self.countdown <= 0.0 => Stage::Pretenst,
+                _ => return,
+            };
+            self.enter(next);
+        }
+    }
+
+    fn enter(&mut self, stage: Stage) {
+        let physics = self.physics.clone();
+        self.countdown = match stage {
+            Stage::Growing | Stage::Pretenst => 0.0,
+            Stage::Shaping => physics.interval_countdown * SHAPING_COUNTDOWNS,
+            Stage::Slack => physics.interval_countdown,
+            Stage::Pretensing => physics.pretensing_countdown,
+        };
+        match stage {
+            Stage::Shaping => {
+                (self.joins, self.unjoined) = start_shaping(&mut self.simulation);
+                if !self.joins.is_empty() {
+                    *self.simulation.physics_mut() = physics.shaping();
+                    self.slack_lengths = self.push_lengths();
+                    for &(push, length) in &self.slack_lengths {
+                        self.simulation.change_length(push, length * (1.0 + physics.shaping_pretenst));
+                    }
+                }
+            }
+            Stage::Slack => {
+                let abandoned = abandon_shaping(&mut self.simulation, std::mem::take(&mut self.joins));
+                self.unjoined.extend(abandoned);
+                *self.simulation.physics_mut() = physics;
+                for (push, length) in std::mem::take(&mut self.slack_lengths) {
+                    self.simulation.change_length(push, length);
+                }
+            }
+            Stage::Pretensing => {
+                for (push, length) in self.push_lengths() {
+                    self.simulation.change_length_over(push, length * (1.0 + physics.pretenst), physics.pretensing_countdown);
+                }
+            }
+            Stage::Growing | Stage::Pretenst => {}
+        }
+        let iteration = self.simulation.iterations();
+        self.events.push(StageChange { from: self.stage, to: stage, iteration });
+        self.stage = stage;
+    }
+
+    fn push_lengths(&self) -> Vec<(IntervalId, f64)> {
+        self.simulation.fabric().intervals()
+            .filter(|(_, interval)| interval.role == Role::Push)
+            .map(|(id, interval)| (id, interval.ideal_length))
+            .collect()
+    }
+}
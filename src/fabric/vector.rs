@@ -0,0 +1,114 @@
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// A point or direction in space, in meters. Up is positive y.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Vector {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vector {
+    pub const ZERO: Vector = Vector { x: 0.0, y: 0.0, z: 0.0 };
+    pub const UP: Vector = Vector { x: 0.0, y: 1.0, z: 0.0 };
+
+    pub const fn new(x: f64, y: f64, z: f64) -> Vector {
+        Vector { x, y, z }
+    }
+
+    pub fn dot(self, other: Vector) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vector) -> Vector {
+        Vector {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn magnitude(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// The same direction with a magnitude of one. The zero vector stays zero.
+    pub fn normalized(self) -> Vector {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 {
+            return self;
+        }
+        self / magnitude
+    }
+
+    pub fn distance(self, other: Vector) -> f64 {
+        (other - self).magnitude()
+    }
+}
+
+impl Display for Vector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Vector { x, y, z } = self;
+        write!(f, "({x:.3}, {y:.3}, {z:.3})")
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, other: Vector) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, factor: f64) -> Vector {
+        Vector::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Div<f64> for Vector {
+    type Output = Vector;
+
+    fn div(self, divisor: f64) -> Vector {
+        Vector::new(self.x / divisor, self.y / divisor, self.z / divisor)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Sum for Vector {
+    fn sum<I: Iterator<Item=Vector>>(iter: I) -> Vector {
+        iter.fold(Vector::ZERO, Add::add)
+    }
+}
@@ -0,0 +1,93 @@
+use crate::fabric::{Fabric, Interval, JointId, Role};
+use crate::fabric::brick::{Brick, PULL_STIFFNESS};
+use crate::fabric::growth::Build;
+use crate::interpreter::VulcanizeType;
+
+/// Adds the pulls that stiffen the joins between bricks, and returns how many were added.
+/// Pulls that are already there are not added again, so vulcanizing twice adds nothing the
+/// second time.
+///
+/// - [VulcanizeType::Bowtie] ties each pair of neighbouring bricks together with three
+///   short pulls, joining the closest joints of the two apart from those they share.
+/// - [VulcanizeType::Snelson] crosses each join with pulls from the far end of each push
+///   meeting it in one brick to the far end of the next push meeting it in the other.
+///
+/// ```
+/// use tenscript::fabric::growth::grow;
+/// use tenscript::fabric::vulcanize::vulcanize;
+/// use tenscript::VulcanizeType;
+///
+/// let plan = tenscript::interpret("(fabric (build (seed :left) (grow A+ 2)))").unwrap();
+/// let mut build = grow(&plan).unwrap();
+/// assert_eq!(vulcanize(&mut build, VulcanizeType::Bowtie), 6);
+/// assert_eq!(vulcanize(&mut build, VulcanizeType::Bowtie), 0);
+/// assert_eq!(vulcanize(&mut build, VulcanizeType::Snelson), 6);
+/// ```
+pub fn vulcanize(build: &mut Build, vulcanize_type: VulcanizeType) -> usize {
+    let fabric = build.fabric();
+    let pulls: Vec<_> = neighbours(build.bricks())
+        .flat_map(|(a, b)| match vulcanize_type {
+            VulcanizeType::Bowtie => bowtie(fabric, a, b),
+            VulcanizeType::Snelson => snelson(fabric, a, b),
+        })
+        .collect();
+    let fabric = build.fabric_mut();
+    let mut added = 0;
+    for (alpha, omega) in pulls {
+        if fabric.interval_between(alpha, omega).is_some() {
+            continue;
+        }
+        let ideal_length = fabric.position(alpha).distance(fabric.position(omega));
+        fabric.create_interval(Interval { alpha, omega, role: Role::Pull, ideal_length, stiffness: PULL_STIFFNESS });
+        added += 1;
+    }
+    added
+}
+
+/// Bricks that share a face.
+fn neighbours(bricks: &[Brick]) -> impl Iterator<Item=(&Brick, &Brick)> {
+    bricks.iter().enumerate().flat_map(move |(i, a)| {
+        bricks[i + 1..].iter()
+            .filter(move |b| shared_joints(a, b).len() == 3)
+            .map(move |b| (a, b))
+    })
+}
+
+fn shared_joints(a: &Brick, b: &Brick) -> Vec<JointId> {
+    a.joints().iter().copied().filter(|joint| b.joints().contains(joint)).collect()
+}
+
+fn bowtie(fabric: &Fabric, a: &Brick, b: &Brick) -> Vec<(JointId, JointId)> {
+    let shared = shared_joints(a, b);
+    let own = |brick: &Brick| brick.joints().iter().copied()
+        .filter(|joint| !shared.contains(joint))
+        .collect::<Vec<_>>();
+    let mut candidates = Vec::new();
+    for alpha in own(a) {
+        for omega in own(b) {
+            candidates.push((fabric.position(alpha).distance(fabric.position(omega)), alpha, omega));
+        }
+    }
+    candidates.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+    let mut pulls: Vec<(JointId, JointId)> = Vec::new();
+    for (_, alpha, omega) in candidates {
+        if pulls.len() == 3 {
+            break;
+        }
+        if pulls.iter().all(|&(a, b)| a != alpha && b != omega) {
+            pulls.push((alpha, omega));
+        }
+    }
+    pulls
+}
+
+fn snelson(fabric: &Fabric, a: &Brick, b: &Brick) -> Vec<(JointId, JointId)> {
+    let far_end = |brick: &Brick, joint: JointId| brick.pushes().iter()
+        .map(|&push| &fabric[push])
+        .find(|push| push.touches(joint))
+        .map(|push| push.other_joint(joint));
+    let shared = shared_joints(a, b);
+    (0..shared.len())
+        .filter_map(|i| Some((far_end(a, shared[i])?, far_end(b, shared[(i + 1) % shared.len()])?)))
+        .collect()
+}
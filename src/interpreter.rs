@@ -1,9 +1,50 @@
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Display, Formatter};
 
 use crate::error::Error;
+use crate::interpreter::params::{Param, Value};
+use crate::quantity::{Length, Percent};
+use crate::scanner::Location;
 use crate::sexp;
 use crate::sexp::Sexp;
 
+pub mod diff;
+pub mod features;
+pub mod fingerprint;
+pub mod lint;
+pub mod params;
+pub mod plan_builder;
+pub mod random;
+pub mod schema;
+pub mod unparse;
+pub mod validate;
+
+pub use features::Features;
+
+/// Ties each variant of a keyword enum to the atom it is written as, so that parsing,
+/// printing and the [schema] all work from the same list.
+macro_rules! atoms {
+    ($enum:ident { $($variant:ident = $name:literal,)+ }) => {
+        impl $enum {
+            pub const NAMES: &'static [&'static str] = &[$($name),+];
+            const EXPECTED: &'static str = stringify!($($name)|+);
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($enum::$variant => $name,)+
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<$enum> {
+                match name {
+                    $($name => Some($enum::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum FaceName {
     Seed,
@@ -17,6 +58,23 @@ pub enum FaceName {
     Dminus,
 }
 
+impl FaceName {
+    /// The face on the other side of the brick. The seed face has no opposite.
+    pub fn opposite(self) -> FaceName {
+        match self {
+            FaceName::Seed => FaceName::Seed,
+            FaceName::Aplus => FaceName::Aminus,
+            FaceName::Bplus => FaceName::Bminus,
+            FaceName::Cplus => FaceName::Cminus,
+            FaceName::Dplus => FaceName::Dminus,
+            FaceName::Aminus => FaceName::Aplus,
+            FaceName::Bminus => FaceName::Bplus,
+            FaceName::Cminus => FaceName::Cplus,
+            FaceName::Dminus => FaceName::Dplus,
+        }
+    }
+}
+
 impl Display for FaceName {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
@@ -33,21 +91,53 @@ impl Display for FaceName {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum VulcanizeType {
     Bowtie,
     Snelson,
 }
 
+atoms!(VulcanizeType {
+    Bowtie = "bowtie",
+    Snelson = "snelson",
+});
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum SurfaceCharacter {
     Frozen,
     Bouncy,
     Sticky,
 }
 
-#[derive(Debug, Clone)]
+atoms!(SurfaceCharacter {
+    Frozen = "frozen",
+    Bouncy = "bouncy",
+    Sticky = "sticky",
+});
+
+/// The ground a fabric stands on: one of the named characters, or one tuned by hand to
+/// match a real floor with `(surface (friction 40%) (restitution 20%) (height 0))`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Surface {
+    Character(SurfaceCharacter),
+    Tuned {
+        /// How hard the ground holds back a joint sliding on it, as a fraction of how hard it
+        /// pushes the joint up.
+        friction: Percent,
+        /// How much of the push back of the ground a joint gets when it rises out of it.
+        restitution: Percent,
+        /// Where the ground is.
+        height: Length,
+    },
+}
+
+impl From<SurfaceCharacter> for Surface {
+    fn from(character: SurfaceCharacter) -> Self {
+        Surface::Character(character)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum SeedType {
     Left,
     LeftRight,
@@ -55,17 +145,36 @@ pub enum SeedType {
     RightLeft,
 }
 
-#[derive(Debug, Clone)]
+atoms!(SeedType {
+    Left = "left",
+    LeftRight = "left-right",
+    Right = "right",
+    RightLeft = "right-left",
+});
+
+/// Names a face of a grown brick so that the shaping phase can refer to it.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Mark {
     face: FaceName,
     name: String,
 }
 
-#[derive(Debug, Clone)]
+impl Mark {
+    pub fn face(&self) -> FaceName {
+        self.face
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum TenscriptNode {
     Grow {
         face: FaceName,
-        forward: String,
+        /// How many bricks are grown straight out of the face.
+        forward_count: usize,
         branch: Option<Box<TenscriptNode>>,
         marks: Vec<Mark>,
     },
@@ -74,48 +183,325 @@ pub enum TenscriptNode {
     },
 }
 
-#[derive(Debug, Clone, Default)]
+impl TenscriptNode {
+    /// Every grow in the tree, depth first, in the order they are written.
+    pub fn grows(&self) -> Grows<'_> {
+        Grows { stack: vec![(NodePath::default(), self)] }
+    }
+}
+
+/// One grow in a growth tree, as visited by [TenscriptNode::grows].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grow<'a> {
+    path: NodePath,
+    face: FaceName,
+    forward_count: usize,
+    marks: &'a [Mark],
+    branch: Option<&'a TenscriptNode>,
+}
+
+impl<'a> Grow<'a> {
+    /// The faces grown from on the way here, ending with this grow's own face.
+    pub fn path(&self) -> &NodePath {
+        &self.path
+    }
+
+    pub fn face(&self) -> FaceName {
+        self.face
+    }
+
+    /// How many bricks are grown straight out of the face.
+    pub fn forward_count(&self) -> usize {
+        self.forward_count
+    }
+
+    pub fn marks(&self) -> &'a [Mark] {
+        self.marks
+    }
+
+    /// The growth continuing from the last brick, if any.
+    pub fn branch(&self) -> Option<&'a TenscriptNode> {
+        self.branch
+    }
+}
+
+pub struct Grows<'a> {
+    stack: Vec<(NodePath, &'a TenscriptNode)>,
+}
+
+impl<'a> Iterator for Grows<'a> {
+    type Item = Grow<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((parent, node)) = self.stack.pop() {
+            match node {
+                TenscriptNode::Grow { face, forward_count, branch, marks } => {
+                    let path = parent.child(*face);
+                    if let Some(branch) = branch {
+                        self.stack.push((path.clone(), branch));
+                    }
+                    return Some(Grow {
+                        path,
+                        face: *face,
+                        forward_count: *forward_count,
+                        marks,
+                        branch: branch.as_deref(),
+                    });
+                }
+                TenscriptNode::Branch { subtrees } => {
+                    self.stack.extend(subtrees.iter().rev().map(|subtree| (parent.clone(), subtree)));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Identifies a grow in a growth tree by the faces grown from on the way to it.
+/// Faces are unique within a branch, so the path is unambiguous.
+#[derive(Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct NodePath(Vec<FaceName>);
+
+impl NodePath {
+    pub fn child(&self, face: FaceName) -> NodePath {
+        let mut faces = self.0.clone();
+        faces.push(face);
+        NodePath(faces)
+    }
+
+    pub fn faces(&self) -> &[FaceName] {
+        &self.0
+    }
+}
+
+impl Display for NodePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("growth");
+        }
+        for (i, face) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" > ")?;
+            }
+            write!(f, "grow {face}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct BuildPhase {
     seed: Option<SeedType>,
-    scale: Option<f64>,
+    scale: Option<Percent>,
     vulcanize: Option<VulcanizeType>,
     growth: Option<TenscriptNode>,
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct Features {
-    iterations_per_frame: Option<u32>,
-    visual_strain: Option<f64>,
-    gravity: Option<f64>,
-    pretenst_factor: Option<f64>,
-    stiffness_factor: Option<f64>,
-    push_over_pull: Option<f64>,
-    drag: Option<f64>,
-    shaping_pretenst_factor: Option<f64>,
-    shaping_drag: Option<f64>,
-    shaping_stiffness_factor: Option<f64>,
-    antigravity: Option<f64>,
-    interval_countdown: Option<f64>,
-    pretensing_countdown: Option<f64>,
+impl BuildPhase {
+    pub fn seed(&self) -> Option<SeedType> {
+        self.seed
+    }
+
+    /// How the seed brick is scaled relative to the fabric scale.
+    pub fn scale(&self) -> Option<Percent> {
+        self.scale
+    }
+
+    pub fn vulcanize(&self) -> Option<VulcanizeType> {
+        self.vulcanize
+    }
+
+    pub fn growth(&self) -> Option<&TenscriptNode> {
+        self.growth.as_ref()
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct FabricPlan {
     name: Option<String>,
-    scale: Option<f64>,
-    surface: Option<SurfaceCharacter>,
+    /// The length of a push in the seed brick.
+    scale: Option<Length>,
+    surface: Option<Surface>,
     features: Features,
     build_phase: BuildPhase,
 }
 
+impl FabricPlan {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The length of a push in the seed brick.
+    pub fn scale(&self) -> Option<Length> {
+        self.scale
+    }
+
+    pub fn surface(&self) -> Option<Surface> {
+        self.surface
+    }
+
+    /// The features this plan sets. Unset features take their values from [Features::defaults].
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    pub fn build_phase(&self) -> &BuildPhase {
+        &self.build_phase
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct InterpretError {
     kind: ErrorKind,
+    location: Option<Location>,
+}
+
+impl InterpretError {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
 }
 
 impl Display for InterpretError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.kind, f)
+        match self.location {
+            Some(location) => write!(f, "{} at {location}", self.kind),
+            None => Display::fmt(&self.kind, f),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found while interpreting, located at the list it was found in when known.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    severity: Severity,
+    kind: ErrorKind,
+    location: Option<Location>,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Diagnostic { severity, kind, location } = self;
+        match location {
+            Some(location) => write!(f, "{severity:?} at {location}: {kind}"),
+            None => write!(f, "{severity:?}: {kind}"),
+        }
+    }
+}
+
+impl From<Diagnostic> for InterpretError {
+    fn from(Diagnostic { kind, location, .. }: Diagnostic) -> Self {
+        InterpretError { kind, location }
+    }
+}
+
+/// A part of a plan whose source location is recorded during interpretation.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum SourceKey {
+    Fabric,
+    /// A `(fabric ..)` property such as `surface` or `features`.
+    Property(&'static str),
+    Feature(String),
+    /// A `(build ..)` property such as `seed` or `vulcanize`.
+    Build(&'static str),
+    Grow(NodePath),
+    Mark(NodePath, usize),
+    Param(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    locations: HashMap<SourceKey, Location>,
+}
+
+impl SourceMap {
+    pub fn location(&self, key: &SourceKey) -> Option<Location> {
+        self.locations.get(key).copied()
+    }
+}
+
+/// A best-effort plan together with everything that went wrong while interpreting it.
+/// Forms that could not be interpreted are left out of the plan.
+#[derive(Debug, Clone)]
+pub struct Interpretation {
+    plan: FabricPlan,
+    diagnostics: Vec<Diagnostic>,
+    source_map: SourceMap,
+    params: Vec<Param>,
+}
+
+impl Interpretation {
+    pub fn plan(&self) -> &FabricPlan {
+        &self.plan
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// The parameters the fabric declares, in the order they are declared.
+    pub fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn lint(&self, lints: &lint::LintSet) -> Vec<lint::Finding> {
+        lint::lint_plan(&self.plan, &self.source_map, lints)
+    }
+
+    pub fn validate(&self) -> Vec<validate::Violation> {
+        validate::validate_plan(&self.plan, &self.source_map)
+    }
+}
+
+/// What the caller decides instead of the source: values for some of its `(param ..)`s,
+/// and the seed for resolving its random forms.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    params: HashMap<String, Value>,
+    random_seed: Option<u64>,
+}
+
+impl Overrides {
+    pub fn param(mut self, name: &str, value: Value) -> Overrides {
+        self.params.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn random_seed(mut self, random_seed: u64) -> Overrides {
+        self.random_seed = Some(random_seed);
+        self
     }
 }
 
@@ -123,17 +509,50 @@ impl Display for InterpretError {
 pub enum ErrorKind {
     Mismatch { rule: &'static str, sexp: Sexp, expected: &'static str },
     BadCall { context: &'static str, expected: &'static str, sexp: Sexp },
-    TypeError { expected: &'static str, sexp: Sexp },
+    TypeError { expected: &'static str, sexp: Sexp, suggestion: Option<&'static str> },
     AlreadyDefined { property: &'static str, sexp: Sexp },
     IllegalRepetition { kind: &'static str, value: String },
     MultipleBranches,
-    IllegalCall { context: &'static str, sexp: Sexp },
+    IllegalCall { context: &'static str, sexp: Sexp, suggestion: Option<&'static str> },
+    ValueOutOfRange { property: &'static str, value: f64, min: f64, max: f64, sexp: Sexp },
+    Unnamed { sexp: Sexp },
+    UnknownBase { name: String },
+    InheritanceCycle { names: Vec<String> },
+    ParamOutOfRange { name: String, value: Value, min: Value, max: Value },
+    /// A value given for a parameter the fabric does not declare.
+    UnknownParam { name: String },
+    Unsupported { context: &'static str, sexp: Sexp },
     Unknown,
 }
 
+impl ErrorKind {
+    /// A close match for a misspelled name, when there is one.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            ErrorKind::TypeError { suggestion, .. } | ErrorKind::IllegalCall { suggestion, .. } => *suggestion,
+            _ => None,
+        }
+    }
+}
+
 impl Display for ErrorKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(self, f)
+        if let ErrorKind::ValueOutOfRange { property, value, min, max, sexp } = self {
+            return write!(f, "{property} must be from {min} to {max} but is {value} in {sexp}");
+        }
+        if let ErrorKind::ParamOutOfRange { name, value, min, max } = self {
+            return write!(f, "param {name} must be from {min} to {max} but is {value}");
+        }
+        // The suggestion is left out here so that it is only given once, below.
+        match self {
+            ErrorKind::TypeError { expected, sexp, .. } => write!(f, "expected {expected} but found {sexp}")?,
+            ErrorKind::IllegalCall { context, sexp, .. } => write!(f, "unknown head {sexp} in {context}")?,
+            _ => Debug::fmt(self, f)?,
+        }
+        if let Some(suggestion) = self.suggestion() {
+            write!(f, " (did you mean {suggestion:?}?)")?;
+        }
+        Ok(())
     }
 }
 
@@ -142,31 +561,104 @@ pub fn interpret(source: &str) -> Result<FabricPlan, Error> {
 }
 
 pub fn interpret_sexp(sexp: &Sexp) -> Result<FabricPlan, Error> {
-    builder::interpret(sexp)
+    interpret_sexp_with_overrides(sexp, &Overrides::default())
+}
+
+/// Interprets the source with some of its `(param ..)` values overridden.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use tenscript::interpreter::{interpret_with_params, params::Value};
+///
+/// let source = "(fabric (param legs 3 (range 1 10)) (build (grow A+ legs)))";
+/// let params = HashMap::from([("legs".to_string(), Value::Integer(5))]);
+/// let plan = interpret_with_params(source, &params).unwrap();
+/// assert_eq!(plan.build_phase().growth().unwrap().grows().next().unwrap().forward_count(), 5);
+/// ```
+pub fn interpret_with_params(source: &str, params: &HashMap<String, Value>) -> Result<FabricPlan, Error> {
+    let overrides = Overrides { params: params.clone(), ..Overrides::default() };
+    interpret_sexp_with_overrides(&sexp::parse(source)?, &overrides)
+}
+
+/// Interprets the source with `(choose ..)`, `(random-int ..)` and `(maybe ..)` resolved
+/// from the given seed, whatever `(random-seed ..)` it declares.
+///
+/// ```
+/// let source = "(fabric (build (grow A+ (random-int 2 6) (maybe 30% (mark B- :leg)))))";
+/// let plan = tenscript::interpreter::interpret_with_random_seed(source, 42).unwrap();
+/// assert_eq!(plan, tenscript::interpreter::interpret_with_random_seed(source, 42).unwrap());
+/// ```
+pub fn interpret_with_random_seed(source: &str, random_seed: u64) -> Result<FabricPlan, Error> {
+    interpret_sexp_with_overrides(&sexp::parse(source)?, &Overrides::default().random_seed(random_seed))
+}
+
+pub fn interpret_sexp_with_overrides(sexp: &Sexp, overrides: &Overrides) -> Result<FabricPlan, Error> {
+    builder::interpret(sexp, overrides)
+        .map_err(Error::InterpretError)
+}
+
+/// Interprets as much of the source as possible, collecting every error and warning
+/// instead of stopping at the first. Only scanning and parsing errors are fatal.
+pub fn interpret_with_diagnostics(source: &str) -> Result<Interpretation, Error> {
+    Ok(interpret_sexp_with_diagnostics(&sexp::parse(source)?))
+}
+
+pub fn interpret_sexp_with_diagnostics(sexp: &Sexp) -> Interpretation {
+    interpret_sexp_with_overrides_and_diagnostics(sexp, &Overrides::default())
+}
+
+pub fn interpret_sexp_with_overrides_and_diagnostics(sexp: &Sexp, overrides: &Overrides) -> Interpretation {
+    builder::interpret_with_diagnostics(sexp, overrides)
+}
+
+/// Interprets every `(fabric ..)` form in the source, keyed by fabric name.
+pub fn interpret_library(source: &str) -> Result<BTreeMap<String, FabricPlan>, Error> {
+    interpret_library_sexps(&sexp::parse_all(source)?)
+}
+
+pub fn interpret_library_sexps(sexps: &[Sexp]) -> Result<BTreeMap<String, FabricPlan>, Error> {
+    builder::interpret_library(sexps)
         .map_err(Error::InterpretError)
 }
 
 mod builder {
-    use std::collections::HashSet;
-    use std::iter::repeat;
+    use std::collections::{BTreeMap, HashMap, HashSet};
+    use std::ops::RangeInclusive;
 
-    use crate::interpreter::{ErrorKind, FabricPlan, FaceName, InterpretError, Mark, SeedType, SurfaceCharacter, TenscriptNode, VulcanizeType};
-    use crate::interpreter::ErrorKind::{AlreadyDefined, BadCall, IllegalCall, IllegalRepetition, Mismatch, MultipleBranches, Unknown};
+    use crate::interpreter::{BuildPhase, Diagnostic, ErrorKind, FabricPlan, Features, FaceName, InterpretError, Interpretation, Mark, NodePath, SeedType, Severity, SourceKey, SourceMap, Surface, SurfaceCharacter, TenscriptNode, VulcanizeType};
+    use crate::interpreter::{features, params, random, schema, Overrides};
+    use crate::interpreter::random::Random;
+    use crate::interpreter::params::{Param, Value};
+    use crate::interpreter::features::FeatureType;
+    use crate::interpreter::ErrorKind::{AlreadyDefined, BadCall, IllegalCall, IllegalRepetition, InheritanceCycle, Mismatch, MultipleBranches, TypeError, Unknown, UnknownBase, UnknownParam, Unnamed, Unsupported, ValueOutOfRange};
+    use crate::quantity::{Length, Percent};
+    use crate::scanner;
+    use crate::scanner::Location;
     use crate::sexp::Sexp;
+    use crate::suggest::did_you_mean;
+
+    /// Meters, for the fabric scale.
+    const LENGTH_SCALE_RANGE: RangeInclusive<f64> = 0.001..=1000.0;
+    /// Percent, for the build phase scale.
+    const PERCENT_SCALE_RANGE: RangeInclusive<f64> = 1.0..=10_000.0;
+    const FORWARD_COUNT_RANGE: RangeInclusive<f64> = 0.0..=1000.0;
+    /// Percent, for the friction and restitution of a tuned surface.
+    const SURFACE_PERCENT_RANGE: RangeInclusive<f64> = 0.0..=100.0;
+    /// Meters, for the height of a tuned surface.
+    const SURFACE_HEIGHT_RANGE: RangeInclusive<f64> = -1000.0..=1000.0;
 
     macro_rules! expect_enum {
-        ($value:expr, { $($name:pat => $enum_val:expr,)+ }) => {
+        ($value:expr, $enum:ident) => {
             {
-                let expected = stringify!($($name)|+);
+                let expected = $enum::EXPECTED;
                 let $crate::sexp::Sexp::Atom(ref name) = $value else {
-                    return Err($crate::interpreter::ErrorKind::TypeError { expected, sexp: $value.clone() })
+                    return Err($crate::interpreter::ErrorKind::TypeError { expected, sexp: $value.clone(), suggestion: None })
                 };
-                match name.as_str() {
-                    $(
-                        $name => $enum_val,
-                    )+
-                    _ => return Err($crate::interpreter::ErrorKind::TypeError { expected, sexp: $value.clone() })
-                }
+                let Some(value) = $enum::from_name(name) else {
+                    let suggestion = did_you_mean(name, $enum::NAMES);
+                    return Err($crate::interpreter::ErrorKind::TypeError { expected, sexp: $value.clone(), suggestion })
+                };
+                value
             }
         }
     }
@@ -176,13 +668,217 @@ mod builder {
         tail: &'a [Sexp],
     }
 
-    pub fn interpret(sexp: &Sexp) -> Result<FabricPlan, InterpretError> {
-        fabric(sexp)
-            .map_err(|kind| InterpretError { kind })
+    /// A fabric plan as written, before `extends` has been resolved against its library.
+    #[derive(Default)]
+    struct Definition {
+        plan: FabricPlan,
+        extends: Option<String>,
+        merge: Option<TenscriptNode>,
+        /// Where the `(fabric ..)` form starts, for errors found while resolving the library.
+        location: Option<Location>,
+    }
+
+    /// What interpretation records besides the plan itself.
+    #[derive(Default)]
+    struct Context {
+        diagnostics: Vec<Diagnostic>,
+        source_map: SourceMap,
+        params: Vec<Param>,
+    }
+
+    impl Context {
+        fn error(&mut self, kind: ErrorKind, sexp: &Sexp) {
+            self.report(Severity::Error, kind, sexp);
+        }
+
+        fn warning(&mut self, kind: ErrorKind, sexp: &Sexp) {
+            self.report(Severity::Warning, kind, sexp);
+        }
+
+        fn report(&mut self, severity: Severity, kind: ErrorKind, sexp: &Sexp) {
+            self.diagnostics.push(Diagnostic { severity, kind, location: sexp.location() });
+        }
+
+        fn locate(&mut self, key: SourceKey, sexp: &Sexp) {
+            if let Some(location) = sexp.location() {
+                self.source_map.locations.insert(key, location);
+            }
+        }
+
+        fn first_error(self) -> Option<InterpretError> {
+            self.diagnostics.into_iter()
+                .find(|diagnostic| diagnostic.severity == Severity::Error)
+                .map(InterpretError::from)
+        }
+    }
+
+    pub fn interpret(sexp: &Sexp, overrides: &Overrides) -> Result<FabricPlan, InterpretError> {
+        let (plan, context) = fabric(sexp, overrides);
+        match context.first_error() {
+            Some(error) => Err(error),
+            None => Ok(plan),
+        }
+    }
+
+    pub fn interpret_with_diagnostics(sexp: &Sexp, overrides: &Overrides) -> Interpretation {
+        let (plan, Context { diagnostics, source_map, params }) = fabric(sexp, overrides);
+        Interpretation { plan, diagnostics, source_map, params }
+    }
+
+    fn fabric(sexp: &Sexp, overrides: &Overrides) -> (FabricPlan, Context) {
+        let mut context = Context::default();
+        context.locate(SourceKey::Fabric, sexp);
+        let plan = match definition(sexp, overrides, &mut context) {
+            Ok(definition) => {
+                if let Some(name) = &definition.extends {
+                    context.error(UnknownBase { name: name.clone() }, sexp);
+                }
+                inherit(None, definition)
+            }
+            Err(kind) => {
+                context.error(kind, sexp);
+                FabricPlan::default()
+            }
+        };
+        (plan, context)
+    }
+
+    pub fn interpret_library(sexps: &[Sexp]) -> Result<BTreeMap<String, FabricPlan>, InterpretError> {
+        let mut context = Context::default();
+        let mut definitions = HashMap::new();
+        let mut names = Vec::new();
+        for sexp in sexps {
+            let definition = match definition(sexp, &Overrides::default(), &mut context) {
+                Ok(definition) => definition,
+                Err(kind) => {
+                    context.error(kind, sexp);
+                    continue;
+                }
+            };
+            let Some(name) = definition.plan.name.clone() else {
+                context.error(Unnamed { sexp: sexp.clone() }, sexp);
+                continue;
+            };
+            if definitions.contains_key(&name) {
+                context.error(IllegalRepetition { kind: "fabric name", value: name }, sexp);
+                continue;
+            }
+            names.push(name.clone());
+            definitions.insert(name, definition);
+        }
+        if let Some(error) = context.first_error() {
+            return Err(error);
+        }
+        let mut plans = BTreeMap::new();
+        for name in names {
+            resolve(&name, &mut definitions, &mut plans, &mut Vec::new())?;
+        }
+        Ok(plans)
+    }
+
+    fn resolve(
+        name: &str,
+        definitions: &mut HashMap<String, Definition>,
+        plans: &mut BTreeMap<String, FabricPlan>,
+        resolving: &mut Vec<String>,
+    ) -> Result<(), InterpretError> {
+        if plans.contains_key(name) {
+            return Ok(());
+        }
+        let definition = &definitions[name];
+        let location = definition.location;
+        if resolving.iter().any(|pending| pending == name) {
+            let mut names = resolving.clone();
+            names.push(name.to_string());
+            return Err(InterpretError { kind: InheritanceCycle { names }, location });
+        }
+        let base = match definition.extends.clone() {
+            None => None,
+            Some(base_name) => {
+                if !plans.contains_key(&base_name) && !definitions.contains_key(&base_name) {
+                    return Err(InterpretError { kind: UnknownBase { name: base_name }, location });
+                }
+                resolving.push(name.to_string());
+                resolve(&base_name, definitions, plans, resolving)?;
+                resolving.pop();
+                plans.get(&base_name).cloned()
+            }
+        };
+        let definition = definitions.remove(name).unwrap();
+        plans.insert(name.to_string(), inherit(base, definition));
+        Ok(())
+    }
+
+    /// Lays a definition over its base plan: every property the definition sets replaces the
+    /// inherited one, features one by one. A `(grow ..)` or `(branch ..)` replaces the inherited
+    /// growth tree, while `(merge ..)` grafts onto it (see [merge_growth]).
+    fn inherit(base: Option<FabricPlan>, Definition { plan, merge, .. }: Definition) -> FabricPlan {
+        let base = base.unwrap_or_default();
+        let FabricPlan { name, scale, surface, features, build_phase } = plan;
+        let BuildPhase { seed, scale: build_scale, vulcanize, growth } = build_phase;
+        let mut growth = growth.or(base.build_phase.growth);
+        if let Some(overlay) = merge {
+            growth = Some(match growth {
+                None => overlay,
+                Some(growth) => merge_growth(growth, overlay),
+            });
+        }
+        FabricPlan {
+            name,
+            scale: scale.or(base.scale),
+            surface: surface.or(base.surface),
+            features: Features::overlay(base.features, features),
+            build_phase: BuildPhase {
+                seed: seed.or(base.build_phase.seed),
+                scale: build_scale.or(base.build_phase.scale),
+                vulcanize: vulcanize.or(base.build_phase.vulcanize),
+                growth,
+            },
+        }
+    }
+
+    /// Grafts an overlay growth tree onto a base tree, matching grows by face name.
+    /// A matched grow takes the overlay's forward count, keeps the base marks followed by any
+    /// new overlay marks, and merges the two branches recursively. Unmatched grows are added.
+    fn merge_growth(base: TenscriptNode, overlay: TenscriptNode) -> TenscriptNode {
+        let base_is_grow = matches!(base, TenscriptNode::Grow { .. });
+        let mut subtrees = grows(base);
+        for grow in grows(overlay) {
+            let TenscriptNode::Grow { face, forward_count, branch, marks } = grow else {
+                unreachable!()
+            };
+            let existing = subtrees.iter_mut().find(|subtree| matches!(subtree, TenscriptNode::Grow { face: existing, .. } if *existing == face));
+            let Some(TenscriptNode::Grow { forward_count: base_forward_count, branch: base_branch, marks: base_marks, .. }) = existing else {
+                subtrees.push(TenscriptNode::Grow { face, forward_count, branch, marks });
+                continue;
+            };
+            *base_forward_count = forward_count;
+            for mark in marks {
+                if !base_marks.iter().any(|existing| existing.face == mark.face && existing.name == mark.name) {
+                    base_marks.push(mark);
+                }
+            }
+            *base_branch = match (base_branch.take(), branch) {
+                (Some(base_branch), Some(branch)) => Some(Box::new(merge_growth(*base_branch, *branch))),
+                (base_branch, branch) => branch.or(base_branch),
+            };
+        }
+        if base_is_grow && subtrees.len() == 1 {
+            subtrees.pop().unwrap()
+        } else {
+            TenscriptNode::Branch { subtrees }
+        }
+    }
+
+    fn grows(node: TenscriptNode) -> Vec<TenscriptNode> {
+        match node {
+            grow @ TenscriptNode::Grow { .. } => vec![grow],
+            TenscriptNode::Branch { subtrees } => subtrees,
+        }
     }
 
     fn expect_call<'a>(rule: &'static str, sexp: &'a Sexp) -> Result<Call<'a>, ErrorKind> {
-        let Sexp::List(ref terms) = sexp else {
+        let Sexp::List(ref terms, _) = sexp else {
             return Err(Mismatch { rule, expected: "( .. )", sexp: sexp.clone() });
         };
         let [ref head, ref tail @ ..] = terms[..] else {
@@ -197,116 +893,275 @@ mod builder {
         })
     }
 
-    fn fabric(sexp: &Sexp) -> Result<FabricPlan, ErrorKind> {
+    fn definition(sexp: &Sexp, overrides: &Overrides, context: &mut Context) -> Result<Definition, ErrorKind> {
         let Call { head: "fabric", tail } = expect_call("fabric", sexp)? else {
             return Err(Mismatch { rule: "fabric", expected: "(fabric ..)", sexp: sexp.clone() });
         };
 
-        let mut fabric = FabricPlan::default();
+        let values = declare_params(sexp, tail, &overrides.params, context);
+        let mut random = Random::new(overrides.random_seed.unwrap_or_else(|| declared_random_seed(tail, context)));
+        let mut definition = Definition { location: sexp.location(), ..Definition::default() };
         for sexp in tail {
-            let Call { head, tail } = expect_call("fabric", sexp)?;
-            match head {
-                "scale" => {
-                    if fabric.scale.is_some() {
-                        return Err(AlreadyDefined { property: "scale", sexp: sexp.clone() });
-                    };
-                    let &[Sexp::Percent(scale)] = tail else {
-                        return Err(BadCall { context: "fabric plan", expected: "(scale <percent>)", sexp: sexp.clone() });
-                    };
-                    fabric.scale = Some(scale / 100.0);
+            let sexp = &params::substitute(sexp, &values);
+            let resolved = match random::resolve(sexp, &mut random) {
+                Ok(resolved) => resolved,
+                Err(kind) => {
+                    context.error(kind, sexp);
+                    continue;
                 }
-                "surface" => {
-                    if fabric.surface.is_some() {
-                        return Err(AlreadyDefined { property: "surface", sexp: sexp.clone() });
-                    };
-                    let &[ref value] = tail else {
-                        return Err(BadCall { context: "fabric plan", expected: "(surface <value>)", sexp: sexp.clone() });
-                    };
-                    let surface = expect_enum!(value, {
-                        "bouncy" => SurfaceCharacter::Bouncy,
-                        "frozen" => SurfaceCharacter::Frozen,
-                        "sticky" => SurfaceCharacter::Sticky,
-                    });
-                    fabric.surface = Some(surface);
+            };
+            for sexp in &resolved {
+                if let Err(kind) = fabric_property(&mut definition, sexp, context) {
+                    context.error(kind, sexp);
                 }
-                "name" => {
-                    if fabric.name.is_some() {
-                        return Err(AlreadyDefined { property: "name", sexp: sexp.clone() });
-                    };
-                    let &[Sexp::String(ref name)] = tail else {
-                        return Err(BadCall { context: "fabric plan", expected: "(name <string>)", sexp: sexp.clone() });
-                    };
-                    fabric.name = Some(name.clone());
+            }
+        }
+        Ok(definition)
+    }
+
+    /// The seed from `(random-seed ..)`, read before anything random is resolved.
+    fn declared_random_seed(tail: &[Sexp], context: &mut Context) -> u64 {
+        let mut random_seed = None;
+        for sexp in tail {
+            let Ok(Call { head: "random-seed", tail }) = expect_call("fabric", sexp) else {
+                continue;
+            };
+            if random_seed.is_some() {
+                context.error(AlreadyDefined { property: "random-seed", sexp: sexp.clone() }, sexp);
+                continue;
+            }
+            let &[Sexp::Integer(seed @ 0..)] = tail else {
+                context.error(BadCall { context: "fabric plan", expected: "(random-seed <non-negative integer>)", sexp: sexp.clone() }, sexp);
+                continue;
+            };
+            random_seed = Some(seed as u64);
+        }
+        random_seed.unwrap_or(random::DEFAULT_RANDOM_SEED)
+    }
+
+    /// Reads every `(param ..)` of a fabric before anything else, so that parameters can be
+    /// used anywhere in it, and settles the value of each.
+    fn declare_params(fabric: &Sexp, tail: &[Sexp], overrides: &HashMap<String, Value>, context: &mut Context) -> HashMap<String, Value> {
+        let mut values = HashMap::new();
+        for sexp in tail {
+            let Ok(Call { head: "param", tail }) = expect_call("fabric", sexp) else {
+                continue;
+            };
+            let param = match params::param(sexp, tail) {
+                Ok(param) => param,
+                Err(kind) => {
+                    context.error(kind, sexp);
+                    continue;
                 }
-                "features" => {
-                    features(&mut fabric, tail)?;
+            };
+            if values.contains_key(param.name()) {
+                context.error(IllegalRepetition { kind: "param", value: param.name().to_string() }, sexp);
+                continue;
+            }
+            context.locate(SourceKey::Param(param.name().to_string()), sexp);
+            let mut value = param.default_value();
+            if let Some(&overridden) = overrides.get(param.name()) {
+                match param.check(overridden) {
+                    Ok(()) => value = overridden,
+                    Err(kind) => context.error(kind, sexp),
                 }
-                "build" => {
-                    build(&mut fabric, tail)?;
+            }
+            values.insert(param.name().to_string(), value);
+            context.params.push(param);
+        }
+        for name in overrides.keys() {
+            if !values.contains_key(name) {
+                context.error(UnknownParam { name: name.clone() }, fabric);
+            }
+        }
+        values
+    }
+
+    fn fabric_property(Definition { plan: fabric, extends, merge, .. }: &mut Definition, sexp: &Sexp, context: &mut Context) -> Result<(), ErrorKind> {
+        let Call { head, tail } = expect_call("fabric", sexp)?;
+        expect_head(&schema::FABRIC, "fabric plan", head, sexp)?;
+        if let Some(property) = schema::FABRIC.head(head).map(schema::Head::name) {
+            context.locate(SourceKey::Property(property), sexp);
+        }
+        match head {
+            "scale" => {
+                if fabric.scale.is_some() {
+                    return Err(AlreadyDefined { property: "scale", sexp: sexp.clone() });
+                };
+                let scale = match *tail {
+                    [Sexp::Quantity(value, unit)] => unit.length(value),
+                    [Sexp::Percent(percent)] => Some(Length::from_meters(1.0).scaled(Percent::new(percent).ratio())),
+                    _ => None,
+                };
+                let Some(scale) = scale else {
+                    return Err(BadCall { context: "fabric plan", expected: "(scale <length>) or (scale <percent of a meter>)", sexp: sexp.clone() });
+                };
+                expect_in_range("scale", scale.meters(), &LENGTH_SCALE_RANGE, sexp)?;
+                fabric.scale = Some(scale);
+            }
+            "surface" => {
+                if fabric.surface.is_some() {
+                    return Err(AlreadyDefined { property: "surface", sexp: sexp.clone() });
+                };
+                let surface = match tail {
+                    [value @ Sexp::Atom(_)] => Surface::Character(expect_enum!(value, SurfaceCharacter)),
+                    [Sexp::List(..), ..] => tuned_surface(tail)?,
+                    _ => return Err(BadCall { context: "fabric plan", expected: "(surface <value>) or (surface <forms>)", sexp: sexp.clone() }),
+                };
+                fabric.surface = Some(surface);
+            }
+            "name" => {
+                if fabric.name.is_some() {
+                    return Err(AlreadyDefined { property: "name", sexp: sexp.clone() });
+                };
+                let &[Sexp::String(ref name)] = tail else {
+                    return Err(BadCall { context: "fabric plan", expected: "(name <string>)", sexp: sexp.clone() });
+                };
+                if !scanner::is_string(name) {
+                    return Err(TypeError { expected: "a string without quotes", sexp: tail[0].clone(), suggestion: None });
                 }
-                "shape" => { todo!() }
-                "pretense" => { todo!() }
-                _ => return Err(IllegalCall { context: "fabric plan", sexp: sexp.clone() })
+                fabric.name = Some(name.clone());
+            }
+            "extends" => {
+                if extends.is_some() {
+                    return Err(AlreadyDefined { property: "extends", sexp: sexp.clone() });
+                };
+                let &[Sexp::String(ref name)] = tail else {
+                    return Err(BadCall { context: "fabric plan", expected: "(extends <string>)", sexp: sexp.clone() });
+                };
+                *extends = Some(name.clone());
+            }
+            "features" => {
+                features(fabric, tail, context);
+            }
+            "build" => {
+                build(fabric, merge, tail, context);
+            }
+            "param" | "random-seed" => {
+                // Already read by declare_params and declared_random_seed.
             }
+            "shape" | "pretense" => {
+                context.warning(Unsupported { context: "fabric plan", sexp: sexp.clone() }, sexp);
+            }
+            _ => unreachable!("{head} is in the schema but not interpreted"),
         }
-        Ok(fabric)
+        Ok(())
     }
 
-    fn build(FabricPlan { build_phase, .. }: &mut FabricPlan, sexps: &[Sexp]) -> Result<(), ErrorKind> {
+    /// Reads the forms of a tuned surface. Whatever is left out is as on a sticky floor at
+    /// height zero: full friction and no restitution.
+    fn tuned_surface(sexps: &[Sexp]) -> Result<Surface, ErrorKind> {
+        let (mut friction, mut restitution, mut height) = (None, None, None);
         for sexp in sexps {
-            let Call { head, tail } = expect_call("build", sexp)?;
+            let Call { head, tail } = expect_call("surface", sexp)?;
+            expect_head(&schema::SURFACE, "surface", head, sexp)?;
             match head {
-                "seed" => {
-                    if build_phase.seed.is_some() {
-                        return Err(AlreadyDefined { property: "seed", sexp: sexp.clone() });
-                    };
-                    let &[ref value] = tail else {
-                        return Err(BadCall { context: "build phase", expected: "(seed <value>)", sexp: sexp.clone() });
-                    };
-                    let seed_type = expect_enum!(value, {
-                        "left" => SeedType::Left,
-                        "left-right" => SeedType::LeftRight,
-                        "right" => SeedType::Right,
-                    });
-                    build_phase.seed = Some(seed_type);
-                }
-                "vulcanize" => {
-                    if build_phase.vulcanize.is_some() {
-                        return Err(AlreadyDefined { property: "vulcanize", sexp: sexp.clone() });
-                    };
-
-                    let &[ref value] = tail else {
-                        return Err(BadCall { context: "build phase", expected: "(vulcanize <value>)", sexp: sexp.clone() });
-                    };
-                    let vulcanize_type = expect_enum!(value, {
-                        "bowtie" => VulcanizeType::Bowtie,
-                        "snelson" => VulcanizeType::Snelson,
-                    });
-                    build_phase.vulcanize = Some(vulcanize_type);
-                }
-                "scale" => {
-                    if build_phase.scale.is_some() {
-                        return Err(AlreadyDefined { property: "scale", sexp: sexp.clone() });
-                    };
+                "friction" | "restitution" => {
                     let &[Sexp::Percent(value)] = tail else {
-                        return Err(BadCall { context: "build phase", expected: "(scale <percent>)", sexp: sexp.clone() });
+                        return Err(BadCall { context: "surface", expected: "(friction <percent>) or (restitution <percent>)", sexp: sexp.clone() });
                     };
-                    build_phase.scale = Some(value);
+                    let (property, slot) = if head == "friction" { ("friction", &mut friction) } else { ("restitution", &mut restitution) };
+                    if slot.is_some() {
+                        return Err(AlreadyDefined { property, sexp: sexp.clone() });
+                    }
+                    expect_in_range(property, value, &SURFACE_PERCENT_RANGE, sexp)?;
+                    *slot = Some(Percent::new(value));
                 }
-                "branch" | "grow" => {
-                    if build_phase.growth.is_some() {
-                        return Err(AlreadyDefined { property: "growth", sexp: sexp.clone() });
+                "height" => {
+                    if height.is_some() {
+                        return Err(AlreadyDefined { property: "height", sexp: sexp.clone() });
+                    }
+                    let meters = match *tail {
+                        [Sexp::Quantity(value, unit)] => unit.length(value).map(Length::meters),
+                        [Sexp::Integer(value)] => Some(value as f64),
+                        [Sexp::Float(value)] => Some(value),
+                        _ => None,
                     };
-                    build_phase.growth = Some(tenscript_node(sexp)?);
+                    let Some(meters) = meters else {
+                        return Err(BadCall { context: "surface", expected: "(height <length>) or (height <meters>)", sexp: sexp.clone() });
+                    };
+                    expect_in_range("height", meters, &SURFACE_HEIGHT_RANGE, sexp)?;
+                    height = Some(Length::from_meters(meters));
                 }
-                _ => return Err(IllegalCall { context: "build phase", sexp: sexp.clone() })
+                _ => unreachable!("{head} is in the schema but not interpreted"),
+            }
+        }
+        Ok(Surface::Tuned {
+            friction: friction.unwrap_or(Percent::new(100.0)),
+            restitution: restitution.unwrap_or(Percent::new(0.0)),
+            height: height.unwrap_or(Length::from_meters(0.0)),
+        })
+    }
+
+    fn build(FabricPlan { build_phase, .. }: &mut FabricPlan, merge: &mut Option<TenscriptNode>, sexps: &[Sexp], context: &mut Context) {
+        for sexp in sexps {
+            if let Err(kind) = build_step(build_phase, merge, sexp, context) {
+                context.error(kind, sexp);
             }
         }
+    }
+
+    fn build_step(build_phase: &mut BuildPhase, merge: &mut Option<TenscriptNode>, sexp: &Sexp, context: &mut Context) -> Result<(), ErrorKind> {
+        let Call { head, tail } = expect_call("build", sexp)?;
+        expect_head(&schema::BUILD, "build phase", head, sexp)?;
+        if let Some(property) = schema::BUILD.head(head).map(schema::Head::name) {
+            context.locate(SourceKey::Build(property), sexp);
+        }
+        match head {
+            "seed" => {
+                if build_phase.seed.is_some() {
+                    return Err(AlreadyDefined { property: "seed", sexp: sexp.clone() });
+                };
+                let [value] = tail else {
+                    return Err(BadCall { context: "build phase", expected: "(seed <value>)", sexp: sexp.clone() });
+                };
+                let seed_type = expect_enum!(value, SeedType);
+                build_phase.seed = Some(seed_type);
+            }
+            "vulcanize" => {
+                if build_phase.vulcanize.is_some() {
+                    return Err(AlreadyDefined { property: "vulcanize", sexp: sexp.clone() });
+                };
+
+                let [value] = tail else {
+                    return Err(BadCall { context: "build phase", expected: "(vulcanize <value>)", sexp: sexp.clone() });
+                };
+                let vulcanize_type = expect_enum!(value, VulcanizeType);
+                build_phase.vulcanize = Some(vulcanize_type);
+            }
+            "scale" => {
+                if build_phase.scale.is_some() {
+                    return Err(AlreadyDefined { property: "scale", sexp: sexp.clone() });
+                };
+                let &[Sexp::Percent(value)] = tail else {
+                    return Err(BadCall { context: "build phase", expected: "(scale <percent>)", sexp: sexp.clone() });
+                };
+                expect_in_range("scale", value, &PERCENT_SCALE_RANGE, sexp)?;
+                build_phase.scale = Some(Percent::new(value));
+            }
+            "branch" | "grow" => {
+                if build_phase.growth.is_some() {
+                    return Err(AlreadyDefined { property: "growth", sexp: sexp.clone() });
+                };
+                build_phase.growth = Some(tenscript_node(sexp, &NodePath::default(), context)?);
+            }
+            "merge" => {
+                if merge.is_some() {
+                    return Err(AlreadyDefined { property: "merge", sexp: sexp.clone() });
+                };
+                let [growth] = tail else {
+                    return Err(BadCall { context: "build phase", expected: "(merge <grow|branch>)", sexp: sexp.clone() });
+                };
+                *merge = Some(tenscript_node(growth, &NodePath::default(), context)?);
+            }
+            _ => unreachable!("{head} is in the schema but not interpreted"),
+        }
         Ok(())
     }
 
-    fn tenscript_node(sexp: &Sexp) -> Result<TenscriptNode, ErrorKind> {
+    fn tenscript_node(sexp: &Sexp, parent: &NodePath, context: &mut Context) -> Result<TenscriptNode, ErrorKind> {
         let Call { head, tail } = expect_call("tenscript_node", sexp)?;
+        expect_head(&schema::GROWTH, "growth", head, sexp)?;
         match head {
             "grow" => {
                 let &[
@@ -317,49 +1172,36 @@ mod builder {
                     return Err(Mismatch { rule: "tenscript_node", expected: "face name and forward count", sexp: sexp.clone() });
                 };
                 let face = expect_face_name(face_atom, face_name)?;
-                let forward = repeat("X").take(forward_count as usize).collect();
+                let path = parent.child(face);
+                context.locate(SourceKey::Grow(path.clone()), sexp);
+                expect_in_range("forward count", forward_count as f64, &FORWARD_COUNT_RANGE, sexp)?;
                 let mut marks = Vec::new();
                 let mut branch = None;
                 for post_growth_op in post_growth {
-                    let Call { head: op_head, tail: op_tail } = expect_call("tenscript_node", post_growth_op)?;
-                    match op_head {
-                        "mark" => {
-                            let &[
-                            ref face_atom @ Sexp::Atom(ref face_name),
-                            Sexp::Atom(ref name),
-                            ] = op_tail else {
-                                return Err(Mismatch { rule: "tenscript_node", expected: "(mark <face_name> <name>)", sexp: post_growth_op.clone() });
-                            };
-                            let face = expect_face_name(face_atom, face_name)?;
-                            marks.push(Mark {
-                                face,
-                                name: name.clone(),
-                            });
-                        }
-                        "branch" => {
-                            if branch.is_some() {
-                                return Err(MultipleBranches);
-                            }
-                            branch = Some(Box::new(tenscript_node(post_growth_op)?));
-                        }
-                        _ => return Err(Mismatch { rule: "tenscript_node", expected: "mark | branch", sexp: sexp.clone() }),
+                    if let Err(kind) = post_growth_step(post_growth_op, &path, &mut marks, &mut branch, context) {
+                        context.error(kind, post_growth_op);
                     }
                 }
-                Ok(TenscriptNode::Grow { face, forward, marks, branch })
+                Ok(TenscriptNode::Grow { face, forward_count: forward_count as usize, marks, branch })
             }
             "branch" => {
                 let mut subtrees = Vec::new();
                 let mut face_exists = HashSet::new();
                 for sub_sexp in tail {
-                    let Call { head: "grow", .. } = expect_call("tenscript_node", sub_sexp)? else {
-                        return Err(Mismatch { rule: "tenscript_node", expected: "(grow ..) under (branch ..)", sexp: sub_sexp.clone() });
+                    let subtree = match branch_subtree(sub_sexp, parent, context) {
+                        Ok(subtree) => subtree,
+                        Err(kind) => {
+                            context.error(kind, sub_sexp);
+                            continue;
+                        }
                     };
-                    let subtree = tenscript_node(sub_sexp)?;
                     let TenscriptNode::Grow { face, .. } = subtree else {
-                        return Err(Unknown);
+                        context.error(Unknown, sub_sexp);
+                        continue;
                     };
                     if face_exists.contains(&face) {
-                        return Err(IllegalRepetition { kind: "face name", value: face.to_string() });
+                        context.error(IllegalRepetition { kind: "face name", value: face.to_string() }, sub_sexp);
+                        continue;
                     }
                     face_exists.insert(face);
 
@@ -367,8 +1209,57 @@ mod builder {
                 }
                 Ok(TenscriptNode::Branch { subtrees })
             }
-            _ => Err(Mismatch { rule: "tenscript_node", expected: "grow | branch", sexp: sexp.clone() }),
+            _ => unreachable!("{head} is in the schema but not interpreted"),
+        }
+    }
+
+    fn branch_subtree(sexp: &Sexp, parent: &NodePath, context: &mut Context) -> Result<TenscriptNode, ErrorKind> {
+        let Call { head: "grow", .. } = expect_call("tenscript_node", sexp)? else {
+            return Err(Mismatch { rule: "tenscript_node", expected: "(grow ..) under (branch ..)", sexp: sexp.clone() });
+        };
+        tenscript_node(sexp, parent, context)
+    }
+
+    fn post_growth_step(sexp: &Sexp, path: &NodePath, marks: &mut Vec<Mark>, branch: &mut Option<Box<TenscriptNode>>, context: &mut Context) -> Result<(), ErrorKind> {
+        let Call { head: op_head, tail: op_tail } = expect_call("tenscript_node", sexp)?;
+        expect_head(&schema::GROW, "grow", op_head, sexp)?;
+        match op_head {
+            "mark" => {
+                let &[
+                ref face_atom @ Sexp::Atom(ref face_name),
+                Sexp::Atom(ref name),
+                ] = op_tail else {
+                    return Err(Mismatch { rule: "tenscript_node", expected: "(mark <face_name> <name>)", sexp: sexp.clone() });
+                };
+                let face = expect_face_name(face_atom, face_name)?;
+                if !scanner::is_name(name) {
+                    return Err(TypeError { expected: "a name of letters, digits, - and +, starting with a letter", sexp: op_tail[1].clone(), suggestion: None });
+                }
+                context.locate(SourceKey::Mark(path.clone(), marks.len()), sexp);
+                marks.push(Mark {
+                    face,
+                    name: name.clone(),
+                });
+            }
+            "branch" => {
+                if branch.is_some() {
+                    return Err(MultipleBranches);
+                }
+                *branch = Some(Box::new(tenscript_node(sexp, path, context)?));
+            }
+            _ => unreachable!("{op_head} is in the schema but not interpreted"),
         }
+        Ok(())
+    }
+
+    /// Checks that the schema allows the head in the context, suggesting a close match if not.
+    /// Every dispatch on heads starts here, so no form is interpreted that the schema does
+    /// not describe.
+    fn expect_head(schema: &schema::Context, context: &'static str, head: &str, sexp: &Sexp) -> Result<(), ErrorKind> {
+        if schema.head(head).is_none() {
+            return Err(IllegalCall { context, sexp: sexp.clone(), suggestion: did_you_mean(head, &schema.head_names()) });
+        }
+        Ok(())
     }
 
     fn expect_face_name(sexp: &Sexp, face_name: &str) -> Result<FaceName, ErrorKind> {
@@ -381,103 +1272,218 @@ mod builder {
             "B-" => FaceName::Bminus,
             "C-" => FaceName::Cminus,
             "D-" => FaceName::Dminus,
-            _ => return Err(Mismatch { rule: "tenscript_node", expected: "unrecognized face name", sexp: sexp.clone() }),
+            _ => return Err(TypeError { expected: "face name", sexp: sexp.clone(), suggestion: did_you_mean(face_name, schema::FACE_NAMES) }),
         })
     }
 
-    fn features(FabricPlan { features, .. }: &mut FabricPlan, sexps: &[Sexp]) -> Result<(), ErrorKind> {
+    fn features(FabricPlan { features, .. }: &mut FabricPlan, sexps: &[Sexp], context: &mut Context) {
         let mut feature_defined = HashSet::new();
         for sexp in sexps {
-            let Call { head: key, tail: &[ref val] } = expect_call("features", sexp)? else {
-                return Err(BadCall { context: "features", expected: "(<feature-name> <value>)", sexp: sexp.clone() });
-            };
-            if feature_defined.contains(key) {
-                return Err(IllegalRepetition { kind: "feature name", value: key.to_string() });
-            }
-            feature_defined.insert(key.to_string());
-            match key {
-                "iterations-per-frame" => {
-                    let Sexp::Integer(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(iterations-per-frame <integer>)", sexp: sexp.clone() });
-                    };
-                    features.iterations_per_frame = Some(*value as u32);
-                }
-                "visual-strain" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(visual-strain <percent>)", sexp: sexp.clone() });
-                    };
-                    features.visual_strain = Some(*value);
-                }
-                "gravity" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(gravity <percent>)", sexp: sexp.clone() });
-                    };
-                    features.gravity = Some(*value);
-                }
-                "pretenst-factor" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(pretenst-factor <percent>)", sexp: sexp.clone() });
-                    };
-                    features.pretenst_factor = Some(*value);
-                }
-                "stiffness-factor" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(stiffness-factor <percent>)", sexp: sexp.clone() });
-                    };
-                    features.stiffness_factor = Some(*value);
-                }
-                "push-over-pull" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(push-over-pull <percent>)", sexp: sexp.clone() });
-                    };
-                    features.push_over_pull = Some(*value);
-                }
-                "drag" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(drag <percent>)", sexp: sexp.clone() });
-                    };
-                    features.drag = Some(*value);
-                }
-                "shaping-pretenst-factor" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(shaping-pretenst-factor <percent>)", sexp: sexp.clone() });
-                    };
-                    features.shaping_pretenst_factor = Some(*value);
-                }
-                "shaping-drag" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(shaping-drag <percent>)", sexp: sexp.clone() });
-                    };
-                    features.shaping_drag = Some(*value);
-                }
-                "shaping-stiffness-factor" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(shaping-stiffness-factor <percent>)", sexp: sexp.clone() });
-                    };
-                    features.shaping_stiffness_factor = Some(*value);
-                }
-                "antigravity" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(antigravity <percent>)", sexp: sexp.clone() });
-                    };
-                    features.antigravity = Some(*value);
-                }
-                "interval-countdown" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(interval-countdown <percent>)", sexp: sexp.clone() });
-                    };
-                    features.interval_countdown = Some(*value);
-                }
-                "pretensing-countdown" => {
-                    let Sexp::Percent(value) = val else {
-                        return Err(Mismatch { rule: "features", expected: "(pretensing-countdown <percent>)", sexp: sexp.clone() });
-                    };
-                    features.pretensing_countdown = Some(*value);
-                }
-                _ => return Err(BadCall { context: "features", expected: "legal feature name", sexp: sexp.clone() }),
+            if let Err(kind) = feature(features, &mut feature_defined, sexp, context) {
+                context.error(kind, sexp);
             }
         }
+    }
+
+    fn feature(features: &mut Features, feature_defined: &mut HashSet<String>, sexp: &Sexp, context: &mut Context) -> Result<(), ErrorKind> {
+        let Call { head: key, tail: [val] } = expect_call("features", sexp)? else {
+            return Err(BadCall { context: "features", expected: "(<feature-name> <value>)", sexp: sexp.clone() });
+        };
+        if feature_defined.contains(key) {
+            return Err(IllegalRepetition { kind: "feature name", value: key.to_string() });
+        }
+        feature_defined.insert(key.to_string());
+        context.locate(SourceKey::Feature(key.to_string()), sexp);
+        let Some(spec) = features::feature(key) else {
+            let feature_names: Vec<_> = features::FEATURES.iter().map(|spec| spec.name()).collect();
+            return Err(IllegalCall { context: "features", sexp: sexp.clone(), suggestion: did_you_mean(key, &feature_names) });
+        };
+        let Some(value) = spec.value_type().parse(val) else {
+            let expected = match spec.value_type() {
+                FeatureType::Integer => "integer",
+                FeatureType::Percent => "percent",
+                FeatureType::Duration => "duration",
+            };
+            return Err(TypeError { expected, sexp: val.clone(), suggestion: None });
+        };
+        expect_in_range(spec.name(), value, spec.range(), sexp)?;
+        spec.set(features, value);
+        Ok(())
+    }
+
+    fn expect_in_range(property: &'static str, value: f64, range: &RangeInclusive<f64>, sexp: &Sexp) -> Result<(), ErrorKind> {
+        if !range.contains(&value) {
+            let (min, max) = (*range.start(), *range.end());
+            return Err(ValueOutOfRange { property, value, min, max, sexp: sexp.clone() });
+        }
         Ok(())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::interpreter::{interpret_library, interpret_with_diagnostics, ErrorKind, FabricPlan, FaceName, InterpretError, SeedType, Severity, SurfaceCharacter};
+    use crate::quantity::Length;
+
+    fn library_error(source: &str) -> InterpretError {
+        match interpret_library(source) {
+            Err(Error::InterpretError(error)) => error,
+            result => panic!("expected an interpret error, got {result:?}"),
+        }
+    }
+
+    /// Each grow as its face, forward count and mark names, in tree order.
+    fn grows(plan: &FabricPlan) -> Vec<(FaceName, usize, Vec<&str>)> {
+        plan.build_phase().growth().into_iter()
+            .flat_map(|growth| growth.grows())
+            .map(|grow| (grow.face(), grow.forward_count(), grow.marks().iter().map(|mark| mark.name()).collect()))
+            .collect()
+    }
+
+    const BASE: &str = r#"
+        (fabric (name "Base") (scale 2m) (surface :frozen)
+         (features (gravity 50%) (drag 20%))
+         (build (seed :left-right) (branch (grow A+ 2 (mark A+ :x)) (grow B+ 1))))
+    "#;
+
+    #[test]
+    fn extends_overrides_properties() {
+        let source = format!(r#"{BASE} (fabric (name "Derived") (extends "Base") (scale 3m) (features (drag 40%)))"#);
+        let library = interpret_library(&source).unwrap();
+        let (base, derived) = (&library["Base"], &library["Derived"]);
+        assert_eq!(derived.name(), Some("Derived"));
+        assert_eq!(derived.scale(), Some(Length::from_meters(3.0)));
+        assert_eq!(derived.surface(), Some(SurfaceCharacter::Frozen.into()));
+        assert_eq!(derived.features().gravity(), base.features().gravity());
+        assert_eq!(derived.features().drag().map(|drag| drag.value()), Some(40.0));
+        assert_eq!(derived.build_phase().seed(), Some(SeedType::LeftRight));
+        assert_eq!(grows(derived), grows(base));
+    }
+
+    #[test]
+    fn extends_replaces_growth() {
+        let source = format!(r#"{BASE} (fabric (name "Derived") (extends "Base") (build (grow C- 4)))"#);
+        let library = interpret_library(&source).unwrap();
+        assert_eq!(grows(&library["Derived"]), [(FaceName::Cminus, 4, vec![])]);
+    }
+
+    #[test]
+    fn extends_merges_growth() {
+        let source = format!(r#"{BASE}
+            (fabric (name "Derived") (extends "Base")
+             (build (merge (branch (grow A+ 3 (mark A+ :x) (mark B+ :y)) (grow C+ 1)))))"#);
+        let library = interpret_library(&source).unwrap();
+        assert_eq!(grows(&library["Derived"]), [
+            (FaceName::Aplus, 3, vec!["x", "y"]),
+            (FaceName::Bplus, 1, vec![]),
+            (FaceName::Cplus, 1, vec![]),
+        ]);
+    }
+
+    #[test]
+    fn extends_cycle_rejected() {
+        let error = library_error(r#"
+            (fabric (name "A") (extends "C"))
+            (fabric (name "B") (extends "A"))
+            (fabric (name "C") (extends "B"))"#);
+        let ErrorKind::InheritanceCycle { names } = error.kind() else {
+            panic!("expected a cycle, got {error}");
+        };
+        assert_eq!(names, &["A", "C", "B", "A"]);
+        assert_eq!(error.location().map(|location| location.line()), Some(2));
+    }
+
+    #[test]
+    fn extends_unknown_base_located() {
+        let error = library_error(r#"
+            (fabric (name "A"))
+            (fabric (name "B") (extends "Missing"))"#);
+        assert!(matches!(error.kind(), ErrorKind::UnknownBase { name } if name == "Missing"), "{error}");
+        assert_eq!(error.location().map(|location| location.line()), Some(3));
+    }
+
+    #[test]
+    fn diagnostics_collect_every_error() {
+        let source = "(fabric\n (features (gravty 50%))\n (build (seed :left)\n  (seed :right)\n  (grow E+ 1)))";
+        let interpretation = interpret_with_diagnostics(source).unwrap();
+        let errors: Vec<_> = interpretation.diagnostics().iter()
+            .map(|diagnostic| (diagnostic.severity(), diagnostic.kind(), diagnostic.location().map(|location| location.line())))
+            .collect();
+        let [
+            (Severity::Error, ErrorKind::IllegalCall { context: "features", .. }, Some(2)),
+            (Severity::Error, ErrorKind::AlreadyDefined { property: "seed", .. }, Some(4)),
+            (Severity::Error, ErrorKind::TypeError { expected: "face name", .. }, Some(5)),
+        ] = errors[..] else {
+            panic!("unexpected diagnostics {errors:?}");
+        };
+        assert_eq!(interpretation.plan().build_phase().seed(), Some(SeedType::Left));
+    }
+
+    #[test]
+    fn suggestion_displayed_once() {
+        let interpretation = interpret_with_diagnostics("(fabric (build (seeed :left) (grow A+ 1 (mark Q+ :x))))").unwrap();
+        let messages: Vec<_> = interpretation.diagnostics().iter().map(|diagnostic| diagnostic.kind().to_string()).collect();
+        assert_eq!(messages, [
+            "unknown head (seeed :left) in build phase (did you mean \"seed\"?)",
+            "expected face name but found :Q+ (did you mean \"A+\"?)",
+        ]);
+    }
+
+    /// The one error interpreting the source gives, which must be a value out of range, as
+    /// its property, value, bounds and line.
+    fn out_of_range(source: &str) -> (&'static str, f64, f64, f64, usize) {
+        let interpretation = interpret_with_diagnostics(source).unwrap();
+        let [diagnostic] = interpretation.diagnostics() else {
+            panic!("expected one diagnostic for {source}, got {:?}", interpretation.diagnostics());
+        };
+        let ErrorKind::ValueOutOfRange { property, value, min, max, .. } = *diagnostic.kind() else {
+            panic!("expected a value out of range for {source}, got {diagnostic}");
+        };
+        (property, value, min, max, diagnostic.location().expect("located error").line())
+    }
+
+    #[test]
+    fn feature_out_of_range() {
+        assert_eq!(out_of_range("(fabric\n (features (iterations-per-frame -5)))"), ("iterations-per-frame", -5.0, 1.0, 100_000.0, 2));
+        assert_eq!(out_of_range("(fabric\n (features\n  (drag -10%)))"), ("drag", -10.0, 0.0, 1000.0, 3));
+    }
+
+    #[test]
+    fn fabric_scale_out_of_range() {
+        assert_eq!(out_of_range("(fabric\n (scale -1m))"), ("scale", -1.0, 0.001, 1000.0, 2));
+        assert_eq!(out_of_range("(fabric (scale 2000m))"), ("scale", 2000.0, 0.001, 1000.0, 1));
+    }
+
+    #[test]
+    fn build_scale_out_of_range() {
+        assert_eq!(out_of_range("(fabric (build\n (scale 0%)))"), ("scale", 0.0, 1.0, 10_000.0, 2));
+    }
+
+    #[test]
+    fn forward_count_out_of_range() {
+        assert_eq!(out_of_range("(fabric (build\n (grow A+ -1)))"), ("forward count", -1.0, 0.0, 1000.0, 2));
+        assert_eq!(out_of_range("(fabric (build (branch (grow B+ 1)\n (grow A+ 5000))))"), ("forward count", 5000.0, 0.0, 1000.0, 2));
+    }
+
+    #[test]
+    fn library_looks_up_plans_by_name() {
+        let library = interpret_library(include_str!("../library.ss")).unwrap();
+        assert_eq!(library["Column"].name(), Some("Column"));
+        assert!(library.values().all(|plan| library[plan.name().unwrap()] == *plan));
+    }
+
+    #[test]
+    fn library_rejects_duplicate_names() {
+        let error = library_error("(fabric (name \"A\"))\n(fabric (name \"B\"))\n(fabric (name \"A\") (scale 2m))");
+        assert!(matches!(error.kind(), ErrorKind::IllegalRepetition { kind: "fabric name", value } if value == "A"), "{error}");
+        assert_eq!(error.location().map(|location| location.line()), Some(3));
+    }
+
+    #[test]
+    fn library_rejects_unnamed_plans() {
+        let error = library_error("(fabric (name \"A\"))\n(fabric (scale 2m))");
+        assert!(matches!(error.kind(), ErrorKind::Unnamed { .. }), "{error}");
+        assert_eq!(error.location().map(|location| location.line()), Some(2));
+    }
+}
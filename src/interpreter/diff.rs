@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::interpreter::{features, BuildPhase, FabricPlan, FaceName, Grow, Mark, NodePath, Surface, TenscriptNode};
+use crate::quantity::Unit;
+use crate::sexp::Sexp;
+
+/// One semantic difference between two plans. Values are given as the tenscript they would
+/// be written as, and `None` means the value is not set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A `(fabric ..)` property such as `name` or `surface`.
+    Property { property: &'static str, before: Option<Sexp>, after: Option<Sexp> },
+    Feature { name: &'static str, before: Option<Sexp>, after: Option<Sexp> },
+    /// A `(build ..)` property such as `seed` or `vulcanize`.
+    Build { property: &'static str, before: Option<Sexp>, after: Option<Sexp> },
+    /// A grow that is new, along with everything growing on from it.
+    GrowAdded { path: NodePath, forward_count: usize },
+    /// A grow that is gone, along with everything that grew on from it.
+    GrowRemoved { path: NodePath, forward_count: usize },
+    ForwardCount { path: NodePath, before: usize, after: usize },
+    MarkAdded { path: NodePath, mark: Mark },
+    MarkRemoved { path: NodePath, mark: Mark },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Property { property, before, after } =>
+                write!(f, "{property}: {} → {}", Value(before), Value(after)),
+            Change::Feature { name, before, after } =>
+                write!(f, "feature {name}: {} → {}", Value(before), Value(after)),
+            Change::Build { property, before, after } =>
+                write!(f, "build {property}: {} → {}", Value(before), Value(after)),
+            Change::GrowAdded { path, forward_count } =>
+                write!(f, "new {} limb forward {forward_count}", Limb(path)),
+            Change::GrowRemoved { path, forward_count } =>
+                write!(f, "removed {} limb forward {forward_count}", Limb(path)),
+            Change::ForwardCount { path, before, after } =>
+                write!(f, "{} limb forward {before} → {after}", Limb(path)),
+            Change::MarkAdded { path, mark } =>
+                write!(f, "new mark :{} on {} of {} limb", mark.name, mark.face, Limb(path)),
+            Change::MarkRemoved { path, mark } =>
+                write!(f, "removed mark :{} on {} of {} limb", mark.name, mark.face, Limb(path)),
+        }
+    }
+}
+
+struct Value<'a>(&'a Option<Sexp>);
+
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(value) => Display::fmt(value, f),
+            None => f.write_str("unset"),
+        }
+    }
+}
+
+struct Limb<'a>(&'a NodePath);
+
+impl Display for Limb<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, face) in self.0.faces().iter().enumerate() {
+            if i > 0 {
+                f.write_str(" > ")?;
+            }
+            Display::fmt(face, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything that differs between two plans: properties and features in canonical order,
+/// then the build phase, with limbs sorted by their paths face by face rather than in the
+/// order they are written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanDiff {
+    changes: Vec<Change>,
+}
+
+impl PlanDiff {
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for PlanDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.changes.is_empty() {
+            return f.write_str("no changes");
+        }
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            Display::fmt(change, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two plans property by property, feature by feature, and limb by limb. Grows are
+/// matched by the faces they grow from, so reordering a branch is not a change.
+///
+/// ```
+/// use tenscript::interpret;
+/// use tenscript::interpreter::diff::plan_diff;
+///
+/// let before = interpret("(fabric (features (gravity 150%)) (build (grow A+ 3)))").unwrap();
+/// let after = interpret("(fabric (features (gravity 120%)) (build (grow A+ 4 (mark B- :hand))))").unwrap();
+/// assert_eq!(plan_diff(&before, &after).to_string(),
+///            "feature gravity: 150% → 120%; A+ limb forward 3 → 4; new mark :hand on B- of A+ limb");
+/// ```
+pub fn plan_diff(before: &FabricPlan, after: &FabricPlan) -> PlanDiff {
+    let mut changes = Vec::new();
+    let properties = [
+        ("name", before.name.clone().map(Sexp::String), after.name.clone().map(Sexp::String)),
+        ("scale", before.scale.map(|scale| Sexp::Quantity(scale.meters(), Unit::Meters)),
+         after.scale.map(|scale| Sexp::Quantity(scale.meters(), Unit::Meters))),
+        ("surface", before.surface.map(surface_value), after.surface.map(surface_value)),
+    ];
+    for (property, before, after) in properties {
+        if before != after {
+            changes.push(Change::Property { property, before, after });
+        }
+    }
+    for spec in features::FEATURES {
+        let (before, after) = (spec.get(&before.features), spec.get(&after.features));
+        if before != after {
+            let value = |value: Option<f64>| value.map(|value| spec.value_type().to_sexp(value));
+            changes.push(Change::Feature { name: spec.name(), before: value(before), after: value(after) });
+        }
+    }
+    build_phase_diff(&before.build_phase, &after.build_phase, &mut changes);
+    PlanDiff { changes }
+}
+
+/// A named surface as its atom, and a tuned one as its whole form.
+fn surface_value(surface: Surface) -> Sexp {
+    match surface {
+        Surface::Character(character) => character.to_sexp(),
+        Surface::Tuned { .. } => surface.to_sexp(),
+    }
+}
+
+fn build_phase_diff(before: &BuildPhase, after: &BuildPhase, changes: &mut Vec<Change>) {
+    let properties = [
+        ("seed", before.seed.map(|seed| seed.to_sexp()), after.seed.map(|seed| seed.to_sexp())),
+        ("scale", before.scale.map(|scale| Sexp::Percent(scale.value())), after.scale.map(|scale| Sexp::Percent(scale.value()))),
+        ("vulcanize", before.vulcanize.map(|vulcanize| vulcanize.to_sexp()), after.vulcanize.map(|vulcanize| vulcanize.to_sexp())),
+    ];
+    for (property, before, after) in properties {
+        if before != after {
+            changes.push(Change::Build { property, before, after });
+        }
+    }
+    let (before, after) = (grows_by_path(before.growth.as_ref()), grows_by_path(after.growth.as_ref()));
+    let mut paths: Vec<&NodePath> = before.keys().chain(after.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    for path in paths {
+        let parent = &path.faces()[..path.faces().len() - 1];
+        match (before.get(path), after.get(path)) {
+            (Some(grow), None) if has_grow(&after, parent) || parent.is_empty() => {
+                changes.push(Change::GrowRemoved { path: path.clone(), forward_count: grow.forward_count() });
+            }
+            (None, Some(grow)) if has_grow(&before, parent) || parent.is_empty() => {
+                changes.push(Change::GrowAdded { path: path.clone(), forward_count: grow.forward_count() });
+            }
+            (Some(before), Some(after)) => grow_diff(before, after, changes),
+            _ => {}
+        }
+    }
+}
+
+fn grow_diff(before: &Grow, after: &Grow, changes: &mut Vec<Change>) {
+    let path = before.path();
+    if before.forward_count() != after.forward_count() {
+        changes.push(Change::ForwardCount { path: path.clone(), before: before.forward_count(), after: after.forward_count() });
+    }
+    for mark in before.marks() {
+        if !after.marks().contains(mark) {
+            changes.push(Change::MarkRemoved { path: path.clone(), mark: mark.clone() });
+        }
+    }
+    for mark in after.marks() {
+        if !before.marks().contains(mark) {
+            changes.push(Change::MarkAdded { path: path.clone(), mark: mark.clone() });
+        }
+    }
+}
+
+fn grows_by_path(growth: Option<&TenscriptNode>) -> BTreeMap<NodePath, Grow<'_>> {
+    growth.into_iter()
+        .flat_map(TenscriptNode::grows)
+        .map(|grow| (grow.path().clone(), grow))
+        .collect()
+}
+
+fn has_grow(grows: &BTreeMap<NodePath, Grow>, faces: &[FaceName]) -> bool {
+    grows.contains_key(&NodePath(faces.to_vec()))
+}
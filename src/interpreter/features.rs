@@ -0,0 +1,294 @@
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+use crate::quantity::{Duration, Percent, Unit};
+use crate::sexp::Sexp;
+
+/// How a feature value is written in tenscript.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FeatureType {
+    Integer,
+    Percent,
+    /// Written with a time unit, such as `4000it` or `2s`, and held in iterations.
+    Duration,
+}
+
+impl FeatureType {
+    /// Reads a value of this type, or nothing if the expression has another type.
+    pub fn parse(self, sexp: &Sexp) -> Option<f64> {
+        match (self, sexp) {
+            (FeatureType::Integer, Sexp::Integer(value)) => Some(*value as f64),
+            (FeatureType::Percent, Sexp::Percent(value)) => Some(*value),
+            (FeatureType::Duration, Sexp::Quantity(value, unit)) => Some(unit.duration(*value)?.iterations()),
+            _ => None,
+        }
+    }
+
+    pub fn to_sexp(self, value: f64) -> Sexp {
+        match self {
+            FeatureType::Integer => Sexp::Integer(value as i64),
+            FeatureType::Percent => Sexp::Percent(value),
+            FeatureType::Duration => Sexp::Quantity(value, Unit::Iterations),
+        }
+    }
+}
+
+impl Display for FeatureType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            FeatureType::Integer => "integer",
+            FeatureType::Percent => "percent",
+            FeatureType::Duration => "duration",
+        })
+    }
+}
+
+/// Everything there is to know about one feature. The [FEATURES] table is the only place
+/// features are declared; parsing, checking, defaults and listings are all driven by it.
+pub struct FeatureSpec {
+    name: &'static str,
+    value_type: FeatureType,
+    range: RangeInclusive<f64>,
+    default: f64,
+    description: &'static str,
+    get: fn(&Features) -> Option<f64>,
+    set: fn(&mut Features, f64),
+}
+
+impl FeatureSpec {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn value_type(&self) -> FeatureType {
+        self.value_type
+    }
+
+    pub fn range(&self) -> &RangeInclusive<f64> {
+        &self.range
+    }
+
+    pub fn default_value(&self) -> f64 {
+        self.default
+    }
+
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    pub fn get(&self, features: &Features) -> Option<f64> {
+        (self.get)(features)
+    }
+
+    pub(crate) fn set(&self, features: &mut Features, value: f64) {
+        (self.set)(features, value)
+    }
+}
+
+impl Display for FeatureSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let FeatureSpec { name, value_type, range, default, description, .. } = self;
+        let default = value_type.to_sexp(*default);
+        let (min, max) = (value_type.to_sexp(*range.start()), value_type.to_sexp(*range.end()));
+        write!(f, "{name:<26}{value_type:<9}{min} to {max}, default {default}: {description}")
+    }
+}
+
+/// Conversion between a feature field and the uniform representation in the table.
+trait FeatureValue: Copy {
+    fn from_f64(value: f64) -> Self;
+
+    fn to_f64(self) -> f64;
+}
+
+impl FeatureValue for u32 {
+    fn from_f64(value: f64) -> Self {
+        value as u32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl FeatureValue for Percent {
+    fn from_f64(value: f64) -> Self {
+        Percent::new(value)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.value()
+    }
+}
+
+impl FeatureValue for Duration {
+    fn from_f64(value: f64) -> Self {
+        Duration::from_iterations(value)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.iterations()
+    }
+}
+
+macro_rules! features {
+    ($($field:ident: $ty:ty = $name:literal $value_type:ident $min:literal..=$max:literal, default $default:literal, $description:literal;)+) => {
+        #[derive(Debug, Clone, Default, PartialEq)]
+        pub struct Features {
+            $($field: Option<$ty>,)+
+        }
+
+        impl Features {
+            $(
+                #[doc = $description]
+                pub fn $field(&self) -> Option<$ty> {
+                    self.$field
+                }
+            )+
+
+            /// Every feature the overlay sets, and the rest from the base.
+            pub fn overlay(base: Features, overlay: Features) -> Features {
+                Features {
+                    $($field: overlay.$field.or(base.$field),)+
+                }
+            }
+        }
+
+        pub const FEATURES: &[FeatureSpec] = &[
+            $(
+                FeatureSpec {
+                    name: $name,
+                    value_type: FeatureType::$value_type,
+                    range: $min as f64..=$max as f64,
+                    default: $default as f64,
+                    description: $description,
+                    get: |features| features.$field.map(FeatureValue::to_f64),
+                    set: |features, value| features.$field = Some(FeatureValue::from_f64(value)),
+                },
+            )+
+        ];
+    }
+}
+
+features! {
+    iterations_per_frame: u32 = "iterations-per-frame" Integer 1..=100_000, default 100,
+        "Simulation iterations between rendered frames.";
+    visual_strain: Percent = "visual-strain" Percent 0..=1000, default 100,
+        "How strongly strain is exaggerated when intervals are drawn.";
+    gravity: Percent = "gravity" Percent 0..=1000, default 100,
+        "Strength of gravity.";
+    pretenst_factor: Percent = "pretenst-factor" Percent 0..=1000, default 100,
+        "How much pushes are lengthened when the fabric is pretensed.";
+    stiffness_factor: Percent = "stiffness-factor" Percent 0..=1000, default 100,
+        "Stiffness of all intervals.";
+    push_over_pull: Percent = "push-over-pull" Percent 1..=1000, default 100,
+        "Stiffness of pushes relative to pulls.";
+    drag: Percent = "drag" Percent 0..=1000, default 100,
+        "Resistance of the surrounding air to joint movement.";
+    shaping_pretenst_factor: Percent = "shaping-pretenst-factor" Percent 0..=1000, default 100,
+        "Pretenst factor while the fabric is being shaped.";
+    shaping_drag: Percent = "shaping-drag" Percent 0..=1000, default 100,
+        "Drag while the fabric is being shaped.";
+    shaping_stiffness_factor: Percent = "shaping-stiffness-factor" Percent 0..=1000, default 100,
+        "Stiffness factor while the fabric is being shaped.";
+    antigravity: Percent = "antigravity" Percent 0..=1000, default 100,
+        "How hard the surface pushes back on joints below it.";
+    interval_countdown: Duration = "interval-countdown" Duration 0..=1_000_000, default 1000,
+        "Time taken for an interval to reach a new length.";
+    pretensing_countdown: Duration = "pretensing-countdown" Duration 0..=1_000_000, default 10_000,
+        "Time taken to pretense the fabric.";
+}
+
+pub fn feature(name: &str) -> Option<&'static FeatureSpec> {
+    FEATURES.iter().find(|spec| spec.name == name)
+}
+
+/// Features whose names start with the given prefix, for completion in editors.
+pub fn complete(prefix: &str) -> impl Iterator<Item=&'static FeatureSpec> + '_ {
+    FEATURES.iter().filter(move |spec| spec.name.starts_with(prefix))
+}
+
+impl Features {
+    /// The values used for features a plan leaves unset.
+    pub fn defaults() -> Features {
+        let mut features = Features::default();
+        for spec in FEATURES {
+            spec.set(&mut features, spec.default);
+        }
+        features
+    }
+
+    /// The features that are set, in table order.
+    pub fn values(&self) -> impl Iterator<Item=(&'static FeatureSpec, f64)> + '_ {
+        FEATURES.iter()
+            .filter_map(|spec| Some((spec, spec.get(self)?)))
+    }
+
+    /// The `(features ..)` form that sets exactly these features.
+    pub fn to_sexp(&self) -> Sexp {
+        let mut terms = vec![Sexp::Ident("features".to_string())];
+        terms.extend(self.values().map(|(spec, value)| {
+            Sexp::List(vec![Sexp::Ident(spec.name.to_string()), spec.value_type.to_sexp(value)], None)
+        }));
+        Sexp::List(terms, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::features::{complete, Features, FEATURES};
+    use crate::interpreter::interpret;
+
+    type Accessor = fn(&Features) -> Option<f64>;
+
+    /// Each feature read through its own accessor rather than through the table.
+    const ACCESSORS: &[(&str, Accessor)] = &[
+        ("iterations-per-frame", |f| f.iterations_per_frame().map(f64::from)),
+        ("visual-strain", |f| f.visual_strain().map(|p| p.value())),
+        ("gravity", |f| f.gravity().map(|p| p.value())),
+        ("pretenst-factor", |f| f.pretenst_factor().map(|p| p.value())),
+        ("stiffness-factor", |f| f.stiffness_factor().map(|p| p.value())),
+        ("push-over-pull", |f| f.push_over_pull().map(|p| p.value())),
+        ("drag", |f| f.drag().map(|p| p.value())),
+        ("shaping-pretenst-factor", |f| f.shaping_pretenst_factor().map(|p| p.value())),
+        ("shaping-drag", |f| f.shaping_drag().map(|p| p.value())),
+        ("shaping-stiffness-factor", |f| f.shaping_stiffness_factor().map(|p| p.value())),
+        ("antigravity", |f| f.antigravity().map(|p| p.value())),
+        ("interval-countdown", |f| f.interval_countdown().map(|d| d.iterations())),
+        ("pretensing-countdown", |f| f.pretensing_countdown().map(|d| d.iterations())),
+    ];
+
+    #[test]
+    fn every_feature_parses_its_default() {
+        assert_eq!(ACCESSORS.len(), FEATURES.len());
+        for spec in FEATURES {
+            let default = spec.value_type().to_sexp(spec.default_value());
+            let source = format!("(fabric (features ({} {default})))", spec.name());
+            let plan = interpret(&source).unwrap_or_else(|error| panic!("{source}: {error}"));
+            let features = plan.features();
+            assert_eq!(spec.get(features), Some(spec.default_value()), "{source}");
+            for (name, accessor) in ACCESSORS {
+                let expected = (*name == spec.name()).then_some(spec.default_value());
+                assert_eq!(accessor(features), expected, "{source} read as {name}");
+            }
+            let round_trip = interpret(&format!("(fabric {})", features.to_sexp())).unwrap();
+            assert_eq!(round_trip.features(), features, "{source}");
+        }
+    }
+
+    #[test]
+    fn defaults_set_every_feature() {
+        let defaults = Features::defaults();
+        for spec in FEATURES {
+            assert_eq!(spec.get(&defaults), Some(spec.default_value()), "{}", spec.name());
+        }
+    }
+
+    #[test]
+    fn completion() {
+        let names: Vec<_> = complete("shaping-").map(|spec| spec.name()).collect();
+        assert_eq!(names, ["shaping-pretenst-factor", "shaping-drag", "shaping-stiffness-factor"]);
+        assert_eq!(complete("").count(), FEATURES.len());
+        assert_eq!(complete("nothing").count(), 0);
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt::{Display, Formatter};
+
+use crate::interpreter::FabricPlan;
+use crate::interpreter::unparse::unparse;
+
+/// Bumped whenever the fingerprint of some plan would come out differently, so that
+/// fingerprints taken before and after the change never compare equal.
+pub const FINGERPRINT_VERSION: u32 = 1;
+
+/// Identifies what a plan will build, independent of how its source was written.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Fingerprint {
+    version: u32,
+    hash: u64,
+}
+
+impl Fingerprint {
+    pub fn version(self) -> u32 {
+        self.version
+    }
+
+    pub fn hash(self) -> u64 {
+        self.hash
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}:{:016x}", self.version, self.hash)
+    }
+}
+
+impl FabricPlan {
+    /// A hash of the canonical form of the plan (see [unparse]), so nothing that is lost in
+    /// interpreting, such as layout or the order features are written in, makes a difference.
+    /// The name is left out too, since it has no effect on the fabric.
+    ///
+    /// The hash is FNV-1a, which unlike the standard library hashers is the same on every
+    /// platform and in every release.
+    ///
+    /// ```
+    /// let written = tenscript::interpret("(fabric (features (gravity 120%) (drag 50%)))").unwrap();
+    /// let reordered = tenscript::interpret("(fabric\n (features\n  (drag 50%)\n  (gravity 120%)))").unwrap();
+    /// assert_eq!(written.fingerprint(), reordered.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> Fingerprint {
+        let canonical = unparse(&FabricPlan { name: None, ..self.clone() });
+        Fingerprint { version: FINGERPRINT_VERSION, hash: fnv1a(canonical.as_bytes()) }
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::interpret;
+
+    /// Pins fingerprints taken with this version. If this fails, the canonical form or the
+    /// hash has changed: bump [FINGERPRINT_VERSION](super::FINGERPRINT_VERSION) and update
+    /// the expected values.
+    #[test]
+    fn fingerprints_are_stable() {
+        let example = interpret(include_str!("../../example.ss")).unwrap();
+        assert_eq!(example.fingerprint().to_string(), "v1:5fced073662d70f6");
+        let empty = interpret("(fabric)").unwrap();
+        assert_eq!(empty.fingerprint().to_string(), "v1:e9b5ca5c54ea508d");
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::interpreter::{BuildPhase, FabricPlan, Features, Mark, NodePath, SourceKey, SourceMap, TenscriptNode};
+use crate::scanner::Location;
+
+/// Beyond this many iterations per frame a plan is almost certainly mistyped.
+const ITERATIONS_PER_FRAME_LIMIT: u32 = 5000;
+
+/// Checks for plans that interpret without error but are probably not what was meant.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Lint {
+    UnusedMark,
+    InconsistentMarkFaces,
+    DefaultFeature,
+    ExcessiveIterations,
+    VulcanizeWithoutGrowth,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedMark,
+        Lint::InconsistentMarkFaces,
+        Lint::DefaultFeature,
+        Lint::ExcessiveIterations,
+        Lint::VulcanizeWithoutGrowth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedMark => "unused-mark",
+            Lint::InconsistentMarkFaces => "inconsistent-mark-faces",
+            Lint::DefaultFeature => "default-feature",
+            Lint::ExcessiveIterations => "excessive-iterations",
+            Lint::VulcanizeWithoutGrowth => "vulcanize-without-growth",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Which lints to run. All of them by default.
+#[derive(Debug, Clone)]
+pub struct LintSet {
+    enabled: BTreeSet<Lint>,
+}
+
+impl Default for LintSet {
+    fn default() -> Self {
+        LintSet::all()
+    }
+}
+
+impl LintSet {
+    pub fn all() -> LintSet {
+        LintSet { enabled: Lint::ALL.into_iter().collect() }
+    }
+
+    pub fn none() -> LintSet {
+        LintSet { enabled: BTreeSet::new() }
+    }
+
+    pub fn enable(mut self, lint: Lint) -> LintSet {
+        self.enabled.insert(lint);
+        self
+    }
+
+    pub fn disable(mut self, lint: Lint) -> LintSet {
+        self.enabled.remove(&lint);
+        self
+    }
+
+    pub fn is_enabled(&self, lint: Lint) -> bool {
+        self.enabled.contains(&lint)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    lint: Lint,
+    message: String,
+    location: Option<Location>,
+}
+
+impl Finding {
+    pub fn lint(&self) -> Lint {
+        self.lint
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Finding { lint, message, location } = self;
+        match location {
+            Some(location) => write!(f, "{lint} at {location}: {message}"),
+            None => write!(f, "{lint}: {message}"),
+        }
+    }
+}
+
+pub fn lint_plan(plan: &FabricPlan, source_map: &SourceMap, lints: &LintSet) -> Vec<Finding> {
+    let mut linter = Linter { source_map, lints, findings: Vec::new() };
+    linter.features(&plan.features);
+    linter.build_phase(&plan.build_phase);
+    if let Some(growth) = &plan.build_phase.growth {
+        linter.growth(growth);
+    }
+    linter.findings
+}
+
+pub fn lint_growth(node: &TenscriptNode, source_map: &SourceMap, lints: &LintSet) -> Vec<Finding> {
+    let mut linter = Linter { source_map, lints, findings: Vec::new() };
+    linter.growth(node);
+    linter.findings
+}
+
+struct Linter<'a> {
+    source_map: &'a SourceMap,
+    lints: &'a LintSet,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, lint: Lint, key: SourceKey, message: String) {
+        if !self.lints.is_enabled(lint) {
+            return;
+        }
+        let location = self.source_map.location(&key);
+        self.findings.push(Finding { lint, message, location });
+    }
+
+    fn features(&mut self, features: &Features) {
+        for (spec, value) in features.values() {
+            if value == spec.default_value() {
+                let name = spec.name();
+                self.report(Lint::DefaultFeature, SourceKey::Feature(name.to_string()),
+                            format!("feature {name} is set to its default value"));
+            }
+        }
+        if let Some(iterations) = features.iterations_per_frame() {
+            if iterations > ITERATIONS_PER_FRAME_LIMIT {
+                self.report(Lint::ExcessiveIterations, SourceKey::Feature("iterations-per-frame".to_string()),
+                            format!("{iterations} iterations per frame is more than {ITERATIONS_PER_FRAME_LIMIT}"));
+            }
+        }
+    }
+
+    fn build_phase(&mut self, BuildPhase { vulcanize, growth, .. }: &BuildPhase) {
+        let Some(vulcanize) = vulcanize else {
+            return;
+        };
+        if !growth.as_ref().is_some_and(grows_bricks) {
+            self.report(Lint::VulcanizeWithoutGrowth, SourceKey::Build("vulcanize"),
+                        format!("vulcanize {vulcanize:?} has no grown bricks to act on"));
+        }
+    }
+
+    fn growth(&mut self, node: &TenscriptNode) {
+        let placed: Vec<_> = node.grows()
+            .flat_map(|grow| {
+                let path = grow.path().clone();
+                grow.marks().iter().enumerate()
+                    .map(move |(index, mark)| PlacedMark { path: path.clone(), index, mark })
+            })
+            .collect();
+        let mut by_name: BTreeMap<&str, Vec<&PlacedMark>> = BTreeMap::new();
+        for placed_mark in &placed {
+            by_name.entry(&placed_mark.mark.name).or_default().push(placed_mark);
+        }
+        for (name, marks) in by_name {
+            let [PlacedMark { path, index, mark }] = marks[..] else {
+                let first = marks[0];
+                for other in &marks[1..] {
+                    if other.mark.face != first.mark.face {
+                        self.report(Lint::InconsistentMarkFaces, SourceKey::Mark(other.path.clone(), other.index),
+                                    format!("mark :{name} is on {} at {} but on {} at {}",
+                                            other.mark.face, other.path, first.mark.face, first.path));
+                    }
+                }
+                continue;
+            };
+            self.report(Lint::UnusedMark, SourceKey::Mark(path.clone(), *index),
+                        format!("mark :{name} on {} at {path} is the only mark with that name", mark.face));
+        }
+    }
+}
+
+struct PlacedMark<'a> {
+    path: NodePath,
+    index: usize,
+    mark: &'a Mark,
+}
+
+fn grows_bricks(node: &TenscriptNode) -> bool {
+    node.grows().any(|grow| grow.forward_count() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::interpret_with_diagnostics;
+    use crate::interpreter::lint::{Lint, LintSet};
+
+    /// Lints the source with the given lints, and returns the line each finding is on.
+    fn lint_lines(source: &str, lints: &LintSet) -> Vec<(Lint, usize)> {
+        interpret_with_diagnostics(source).unwrap()
+            .lint(lints)
+            .iter()
+            .map(|finding| (finding.lint(), finding.location().expect("located finding").line()))
+            .collect()
+    }
+
+    /// The lint finds the problem on the line, and finds nothing once it is disabled.
+    fn assert_lint(lint: Lint, source: &str, line: usize) {
+        assert_eq!(lint_lines(source, &LintSet::none().enable(lint)), [(lint, line)], "{lint} in {source}");
+        assert_eq!(lint_lines(source, &LintSet::all().disable(lint)), [], "{lint} disabled in {source}");
+    }
+
+    #[test]
+    fn unused_mark() {
+        assert_lint(Lint::UnusedMark, "(fabric (build\n (grow A+ 1\n  (mark A+ :lonely))))", 3);
+    }
+
+    #[test]
+    fn inconsistent_mark_faces() {
+        let source = "(fabric (build (branch\n (grow A+ 1 (mark A+ :x))\n (grow B+ 1 (mark B+ :x)))))";
+        assert_lint(Lint::InconsistentMarkFaces, source, 3);
+    }
+
+    #[test]
+    fn default_feature() {
+        assert_lint(Lint::DefaultFeature, "(fabric (features\n (gravity 5%)\n (drag 100%)))", 3);
+    }
+
+    #[test]
+    fn excessive_iterations() {
+        assert_lint(Lint::ExcessiveIterations, "(fabric\n (features\n  (iterations-per-frame 6000)))", 3);
+    }
+
+    #[test]
+    fn vulcanize_without_growth() {
+        assert_lint(Lint::VulcanizeWithoutGrowth, "(fabric (build (seed :left)\n (vulcanize :bowtie)))", 2);
+        assert_lint(Lint::VulcanizeWithoutGrowth, "(fabric (build (grow A+ 0)\n (vulcanize :bowtie)))", 2);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::interpreter::ErrorKind;
+use crate::quantity::Unit;
+use crate::sexp;
+use crate::sexp::Sexp;
+
+/// A value a parameter can take, written as in tenscript.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Percent(f64),
+    Quantity(f64, Unit),
+}
+
+impl Value {
+    pub fn from_sexp(sexp: &Sexp) -> Option<Value> {
+        Some(match *sexp {
+            Sexp::Integer(value) => Value::Integer(value),
+            Sexp::Float(value) => Value::Float(value),
+            Sexp::Percent(value) => Value::Percent(value),
+            Sexp::Quantity(value, unit) => Value::Quantity(value, unit),
+            _ => return None,
+        })
+    }
+
+    /// Reads a value written as in tenscript, such as `5`, `120%` or `30cm`.
+    pub fn parse(source: &str) -> Option<Value> {
+        Value::from_sexp(&sexp::parse(source).ok()?)
+    }
+
+    pub fn to_sexp(self) -> Sexp {
+        match self {
+            Value::Integer(value) => Sexp::Integer(value),
+            Value::Float(value) => Sexp::Float(value),
+            Value::Percent(value) => Sexp::Percent(value),
+            Value::Quantity(value, unit) => Sexp::Quantity(value, unit),
+        }
+    }
+
+    /// What sort of value this is. A parameter only takes values of the same kind as its default.
+    pub fn kind(self) -> &'static str {
+        match self {
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Percent(_) => "percent",
+            Value::Quantity(value, unit) if unit.length(value).is_some() => "length",
+            Value::Quantity(..) => "duration",
+        }
+    }
+
+    /// The value as a plain number, with quantities in meters or iterations so that values
+    /// in different units compare correctly.
+    fn magnitude(self) -> f64 {
+        match self {
+            Value::Integer(value) => value as f64,
+            Value::Float(value) | Value::Percent(value) => value,
+            Value::Quantity(value, unit) => match (unit.length(value), unit.duration(value)) {
+                (Some(length), _) => length.meters(),
+                (None, Some(duration)) => duration.iterations(),
+                (None, None) => value,
+            },
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.to_sexp(), f)
+    }
+}
+
+/// A value declared at the top of a fabric with `(param ..)`, which can be used wherever
+/// a value is expected and overridden without editing the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    name: String,
+    default: Value,
+    range: Option<(Value, Value)>,
+    description: Option<String>,
+}
+
+impl Param {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn default_value(&self) -> Value {
+        self.default
+    }
+
+    pub fn range(&self) -> Option<(Value, Value)> {
+        self.range
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Checks that a value is of the same kind as the default, and within range.
+    pub fn check(&self, value: Value) -> Result<(), ErrorKind> {
+        if value.kind() != self.default.kind() {
+            return Err(ErrorKind::TypeError { expected: self.default.kind(), sexp: value.to_sexp(), suggestion: None });
+        }
+        if let Some((min, max)) = self.range {
+            if !(min.magnitude()..=max.magnitude()).contains(&value.magnitude()) {
+                return Err(ErrorKind::ParamOutOfRange { name: self.name.clone(), value, min, max });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Param { name, default, range, description } = self;
+        write!(f, "{name:<26}{:<9}default {default}", default.kind())?;
+        if let Some((min, max)) = range {
+            write!(f, ", {min} to {max}")?;
+        }
+        if let Some(description) = description {
+            write!(f, ": {description}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the tail of a `(param <name> <value> (range <min> <max>) "<description>")` form,
+/// where the range and description are optional.
+pub(super) fn param(sexp: &Sexp, tail: &[Sexp]) -> Result<Param, ErrorKind> {
+    let bad_call = || ErrorKind::BadCall {
+        context: "fabric plan",
+        expected: "(param <name> <value> (range <min> <max>) \"<description>\")",
+        sexp: sexp.clone(),
+    };
+    let [Sexp::Ident(name), default, rest @ ..] = tail else {
+        return Err(bad_call());
+    };
+    let Some(default) = Value::from_sexp(default) else {
+        return Err(ErrorKind::TypeError { expected: "integer, float, percent or quantity", sexp: default.clone(), suggestion: None });
+    };
+    let mut param = Param { name: name.clone(), default, range: None, description: None };
+    for term in rest {
+        match term {
+            Sexp::List(terms, _) if param.range.is_none() => {
+                let [Sexp::Ident(head), min, max] = &terms[..] else {
+                    return Err(bad_call());
+                };
+                let (Some(min), Some(max)) = (Value::from_sexp(min), Value::from_sexp(max)) else {
+                    return Err(bad_call());
+                };
+                if head != "range" || min.kind() != default.kind() || max.kind() != default.kind() {
+                    return Err(bad_call());
+                }
+                param.range = Some((min, max));
+            }
+            Sexp::String(description) if param.description.is_none() => {
+                param.description = Some(description.clone());
+            }
+            _ => return Err(bad_call()),
+        }
+    }
+    param.check(default)?;
+    Ok(param)
+}
+
+/// Replaces every use of a parameter with its value. Heads are never uses, and neither is
+/// anything inside a `(param ..)` form.
+pub(super) fn substitute(sexp: &Sexp, values: &HashMap<String, Value>) -> Sexp {
+    match sexp {
+        Sexp::List(terms, location) => {
+            if let [Sexp::Ident(head), ..] = &terms[..] {
+                if head == "param" {
+                    return sexp.clone();
+                }
+            }
+            let terms = terms.iter().enumerate()
+                .map(|(index, term)| match term {
+                    Sexp::Ident(name) if index > 0 => match values.get(name) {
+                        Some(value) => value.to_sexp(),
+                        None => term.clone(),
+                    },
+                    _ => substitute(term, values),
+                })
+                .collect();
+            Sexp::List(terms, *location)
+        }
+        _ => sexp.clone(),
+    }
+}
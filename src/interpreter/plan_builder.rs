@@ -0,0 +1,170 @@
+use crate::interpreter::{builder, features, FabricPlan, FaceName, InterpretError, Overrides, SeedType, Surface, VulcanizeType};
+use crate::interpreter::unparse::{atom, call, ident};
+use crate::quantity::{Length, Percent, Unit};
+use crate::sexp::Sexp;
+
+impl FabricPlan {
+    /// Starts a plan to be assembled in code rather than written as tenscript.
+    ///
+    /// ```
+    /// use tenscript::{FabricPlan, FaceName, SeedType, SurfaceCharacter};
+    ///
+    /// let plan = FabricPlan::builder()
+    ///     .name("Knee")
+    ///     .surface(SurfaceCharacter::Bouncy)
+    ///     .seed(SeedType::Left)
+    ///     .grow(FaceName::Aplus, 3, |g| g.mark(FaceName::Aplus, "arm"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(plan.name(), Some("Knee"));
+    /// ```
+    pub fn builder() -> PlanBuilder {
+        PlanBuilder::default()
+    }
+}
+
+/// Assembles the same forms a tenscript author would write, and interprets them on
+/// [build](PlanBuilder::build), so a built plan obeys exactly the rules a written one does:
+/// properties set once, one growth, distinct faces in a branch and one branch per grow.
+#[derive(Debug, Clone, Default)]
+pub struct PlanBuilder {
+    properties: Vec<Sexp>,
+    features: Vec<Sexp>,
+    build: Vec<Sexp>,
+}
+
+impl PlanBuilder {
+    /// The name, which like any tenscript string can have no quotes in it.
+    pub fn name(mut self, name: &str) -> PlanBuilder {
+        self.properties.push(call("name", vec![Sexp::String(name.to_string())]));
+        self
+    }
+
+    /// The length of a push in the seed brick.
+    pub fn scale(mut self, scale: Length) -> PlanBuilder {
+        self.properties.push(call("scale", vec![Sexp::Quantity(scale.meters(), Unit::Meters)]));
+        self
+    }
+
+    pub fn surface(mut self, surface: impl Into<Surface>) -> PlanBuilder {
+        self.properties.push(surface.into().to_sexp());
+        self
+    }
+
+    /// Sets a feature by name, with the value in the feature's own terms: a count,
+    /// a percentage or a number of iterations.
+    pub fn feature(mut self, name: &str, value: f64) -> PlanBuilder {
+        let value = match features::feature(name) {
+            Some(spec) => spec.value_type().to_sexp(value),
+            None => Sexp::Float(value),
+        };
+        self.features.push(call(name, vec![value]));
+        self
+    }
+
+    pub fn seed(mut self, seed: SeedType) -> PlanBuilder {
+        self.build.push(call("seed", vec![seed.to_sexp()]));
+        self
+    }
+
+    /// How the seed brick is scaled relative to the fabric scale.
+    pub fn build_scale(mut self, scale: Percent) -> PlanBuilder {
+        self.build.push(call("scale", vec![Sexp::Percent(scale.value())]));
+        self
+    }
+
+    pub fn vulcanize(mut self, vulcanize: VulcanizeType) -> PlanBuilder {
+        self.build.push(call("vulcanize", vec![vulcanize.to_sexp()]));
+        self
+    }
+
+    /// Grows from a single face of the seed.
+    pub fn grow(mut self, face: FaceName, forward_count: usize, post_growth: impl FnOnce(GrowBuilder) -> GrowBuilder) -> PlanBuilder {
+        self.build.push(grow(face, forward_count, post_growth));
+        self
+    }
+
+    /// Grows from several faces of the seed.
+    pub fn branch(mut self, subtrees: impl FnOnce(BranchBuilder) -> BranchBuilder) -> PlanBuilder {
+        self.build.push(branch(subtrees));
+        self
+    }
+
+    /// The `(fabric ..)` form this builder stands for.
+    pub fn to_sexp(&self) -> Sexp {
+        let PlanBuilder { properties, features, build } = self;
+        let mut terms = vec![ident("fabric")];
+        terms.extend(properties.iter().cloned());
+        if !features.is_empty() {
+            terms.push(call("features", features.clone()));
+        }
+        if !build.is_empty() {
+            terms.push(call("build", build.clone()));
+        }
+        Sexp::List(terms, None)
+    }
+
+    /// The finished plan, or the first rule it breaks, including a name or mark that could
+    /// not be written in tenscript.
+    ///
+    /// ```
+    /// use tenscript::{FabricPlan, FaceName};
+    ///
+    /// let repeated_face = FabricPlan::builder()
+    ///     .branch(|b| b
+    ///         .grow(FaceName::Aplus, 1, |g| g)
+    ///         .grow(FaceName::Aplus, 2, |g| g))
+    ///     .build();
+    /// assert!(repeated_face.is_err());
+    /// assert!(FabricPlan::builder().name("q\"uote").build().is_err());
+    /// assert!(FabricPlan::builder().grow(FaceName::Aplus, 1, |g| g.mark(FaceName::Aplus, "1x")).build().is_err());
+    /// ```
+    pub fn build(self) -> Result<FabricPlan, InterpretError> {
+        builder::interpret(&self.to_sexp(), &Overrides::default())
+    }
+}
+
+/// What follows a grow: marks on the last brick and a branch growing on from it.
+#[derive(Debug, Clone)]
+pub struct GrowBuilder {
+    post_growth: Vec<Sexp>,
+}
+
+impl GrowBuilder {
+    /// Marks a face with a name written the way an atom is: a letter followed by letters,
+    /// digits, `-` and `+`.
+    pub fn mark(mut self, face: FaceName, name: &str) -> GrowBuilder {
+        self.post_growth.push(call("mark", vec![face.to_sexp(), atom(name)]));
+        self
+    }
+
+    pub fn branch(mut self, subtrees: impl FnOnce(BranchBuilder) -> BranchBuilder) -> GrowBuilder {
+        self.post_growth.push(branch(subtrees));
+        self
+    }
+}
+
+/// The grows of a branch, each from a different face.
+#[derive(Debug, Clone)]
+pub struct BranchBuilder {
+    subtrees: Vec<Sexp>,
+}
+
+impl BranchBuilder {
+    pub fn grow(mut self, face: FaceName, forward_count: usize, post_growth: impl FnOnce(GrowBuilder) -> GrowBuilder) -> BranchBuilder {
+        self.subtrees.push(grow(face, forward_count, post_growth));
+        self
+    }
+}
+
+fn grow(face: FaceName, forward_count: usize, post_growth: impl FnOnce(GrowBuilder) -> GrowBuilder) -> Sexp {
+    let GrowBuilder { post_growth } = post_growth(GrowBuilder { post_growth: Vec::new() });
+    let mut terms = vec![face.to_sexp(), Sexp::Integer(forward_count as i64)];
+    terms.extend(post_growth);
+    call("grow", terms)
+}
+
+fn branch(subtrees: impl FnOnce(BranchBuilder) -> BranchBuilder) -> Sexp {
+    let BranchBuilder { subtrees } = subtrees(BranchBuilder { subtrees: Vec::new() });
+    call("branch", subtrees)
+}
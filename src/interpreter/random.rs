@@ -0,0 +1,92 @@
+use crate::interpreter::ErrorKind;
+use crate::sexp::Sexp;
+
+/// The seed used when neither the fabric nor the caller gives one.
+pub const DEFAULT_RANDOM_SEED: u64 = 0;
+
+/// SplitMix64, chosen because it is tiny and gives the same sequence on every platform,
+/// so a seed always resolves a fabric the same way.
+///
+/// ```
+/// use tenscript::interpreter::random::Random;
+///
+/// // The published SplitMix64 sequence, which must never change.
+/// let mut random = Random::new(0);
+/// assert_eq!(random.next_u64(), 0xe220a8397b1dcdaf);
+/// assert_eq!(random.next_u64(), 0x6e789e6aa1b965f4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A whole number from `min` to `max` inclusive.
+    pub fn int_in(&mut self, min: i64, max: i64) -> i64 {
+        let span = (max as i128 - min as i128 + 1) as u128;
+        (min as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    /// True with the given percent chance.
+    pub fn chance(&mut self, percent: f64) -> bool {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        unit * 100.0 < percent
+    }
+}
+
+/// Resolves every `(choose ..)`, `(random-int ..)` and `(maybe ..)` in a term, depth first
+/// in the order they are written. A term can resolve to nothing, when it is a `maybe` that
+/// did not happen.
+pub(super) fn resolve(sexp: &Sexp, random: &mut Random) -> Result<Vec<Sexp>, ErrorKind> {
+    let Sexp::List(terms, location) = sexp else {
+        return Ok(vec![sexp.clone()]);
+    };
+    let bad_call = |expected| ErrorKind::BadCall { context: "random", expected, sexp: sexp.clone() };
+    match &terms[..] {
+        [Sexp::Ident(head), tail @ ..] if head == "choose" => {
+            if tail.is_empty() {
+                return Err(bad_call("(choose <alternative> ..)"));
+            }
+            let chosen = random.int_in(0, tail.len() as i64 - 1);
+            resolve(&tail[chosen as usize], random)
+        }
+        [Sexp::Ident(head), tail @ ..] if head == "random-int" => {
+            let &[Sexp::Integer(min), Sexp::Integer(max)] = tail else {
+                return Err(bad_call("(random-int <min> <max>)"));
+            };
+            if min > max {
+                return Err(bad_call("(random-int <min> <max>) with min no more than max"));
+            }
+            Ok(vec![Sexp::Integer(random.int_in(min, max))])
+        }
+        [Sexp::Ident(head), tail @ ..] if head == "maybe" => {
+            let [Sexp::Percent(percent), term] = tail else {
+                return Err(bad_call("(maybe <percent> <term>)"));
+            };
+            if random.chance(*percent) {
+                resolve(term, random)
+            } else {
+                Ok(Vec::new())
+            }
+        }
+        _ => {
+            let mut resolved = Vec::with_capacity(terms.len());
+            for term in terms {
+                resolved.extend(resolve(term, random)?);
+            }
+            Ok(vec![Sexp::List(resolved, *location)])
+        }
+    }
+}
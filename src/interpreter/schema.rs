@@ -0,0 +1,296 @@
+use crate::interpreter::{features, SeedType, SurfaceCharacter, VulcanizeType};
+use crate::interpreter::unparse::{atom, call, ident};
+use crate::sexp::Sexp;
+
+/// The faces a brick can grow from or be marked on.
+pub const FACE_NAMES: &[&str] = &["A+", "B+", "C+", "D+", "A-", "B-", "C-", "D-"];
+
+/// What may follow the head of a form, in order.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Argument {
+    String,
+    Integer,
+    Percent,
+    /// A number with a unit of length, such as `30cm`.
+    Length,
+    /// A number without a unit, such as `0` or `2.5`.
+    Number,
+    /// A name of the author's choosing, written as an atom such as `:arm`.
+    Name,
+    /// A name of the author's choosing, written bare such as `leg-length`.
+    Identifier,
+    /// Any number, with or without a unit.
+    Value,
+    /// Any value or form at all.
+    Term,
+    /// One of [FACE_NAMES].
+    Face,
+    /// One of the given atoms.
+    OneOf(&'static [&'static str]),
+    /// Any one of the given kinds of argument.
+    Either(&'static [Argument]),
+    /// Any number of forms valid in the named context.
+    Forms(&'static str),
+}
+
+impl Argument {
+    pub fn to_sexp(self) -> Sexp {
+        match self {
+            Argument::String => atom("string"),
+            Argument::Integer => atom("integer"),
+            Argument::Percent => atom("percent"),
+            Argument::Length => atom("length"),
+            Argument::Number => atom("number"),
+            Argument::Name => atom("name"),
+            Argument::Identifier => atom("identifier"),
+            Argument::Value => atom("value"),
+            Argument::Term => atom("term"),
+            Argument::Face => call("one-of", FACE_NAMES.iter().map(|&face| atom(face)).collect()),
+            Argument::OneOf(names) => call("one-of", names.iter().map(|&name| atom(name)).collect()),
+            Argument::Either(arguments) => call("either", arguments.iter().map(|argument| argument.to_sexp()).collect()),
+            Argument::Forms(context) => call("forms", vec![ident(context)]),
+        }
+    }
+}
+
+/// A form that may appear in a context, named by its head.
+#[derive(Debug)]
+pub struct Head {
+    name: &'static str,
+    arguments: &'static [Argument],
+    description: &'static str,
+}
+
+impl Head {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn arguments(&self) -> &'static [Argument] {
+        self.arguments
+    }
+
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    pub fn to_sexp(&self) -> Sexp {
+        let mut terms: Vec<_> = self.arguments.iter().map(|argument| argument.to_sexp()).collect();
+        terms.push(Sexp::String(self.description.to_string()));
+        call(self.name, terms)
+    }
+}
+
+/// Where a form may appear, such as directly inside `(fabric ..)` or `(build ..)`, and
+/// which heads are valid there. The interpreter only interprets heads listed here.
+#[derive(Debug)]
+pub struct Context {
+    name: &'static str,
+    heads: &'static [Head],
+}
+
+impl Context {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn heads(&self) -> &'static [Head] {
+        self.heads
+    }
+
+    pub fn head(&self, name: &str) -> Option<&'static Head> {
+        self.heads.iter().find(|head| head.name == name)
+    }
+
+    pub fn head_names(&self) -> Vec<&'static str> {
+        self.heads.iter().map(Head::name).collect()
+    }
+
+    pub fn to_sexp(&self) -> Sexp {
+        call(self.name, self.heads.iter().map(Head::to_sexp).collect())
+    }
+}
+
+const GROW_ARGUMENTS: &[Argument] = &[Argument::Face, Argument::Integer, Argument::Forms("grow")];
+const GROW_DESCRIPTION: &str = "Grows bricks straight out of a face, then marks or branches from the last one.";
+
+pub static FABRIC: Context = Context {
+    name: "fabric",
+    heads: &[
+        Head { name: "name", arguments: &[Argument::String], description: "The name of the fabric, unique in a library." },
+        Head {
+            name: "param",
+            arguments: &[Argument::Identifier, Argument::Value, Argument::Forms("param"), Argument::String],
+            description: "A value that can be used by name anywhere in the fabric and overridden from outside. The range and description are optional.",
+        },
+        Head {
+            name: "random-seed",
+            arguments: &[Argument::Integer],
+            description: "Seeds the random forms, so that the same seed always gives the same fabric.",
+        },
+        Head { name: "extends", arguments: &[Argument::String], description: "The fabric in the same library to start from." },
+        Head {
+            name: "scale",
+            arguments: &[Argument::Either(&[Argument::Length, Argument::Percent])],
+            description: "The length of a push in the seed brick, or a percentage of a meter.",
+        },
+        Head {
+            name: "surface",
+            arguments: &[Argument::Either(&[Argument::OneOf(SurfaceCharacter::NAMES), Argument::Forms("surface")])],
+            description: "How the ground behaves, by name or tuned by hand.",
+        },
+        Head { name: "features", arguments: &[Argument::Forms("features")], description: "Settings for the simulation." },
+        Head { name: "build", arguments: &[Argument::Forms("build")], description: "How the fabric is grown." },
+        Head { name: "shape", arguments: &[], description: "Not supported yet, and ignored with a warning." },
+        Head { name: "pretense", arguments: &[], description: "Not supported yet, and ignored with a warning." },
+    ],
+};
+
+pub static PARAM: Context = Context {
+    name: "param",
+    heads: &[
+        Head { name: "range", arguments: &[Argument::Value, Argument::Value], description: "The lowest and highest values the parameter may take." },
+    ],
+};
+
+pub static SURFACE: Context = Context {
+    name: "surface",
+    heads: &[
+        Head { name: "friction", arguments: &[Argument::Percent], description: "How hard the ground holds back joints sliding on it. Defaults to 100%." },
+        Head { name: "restitution", arguments: &[Argument::Percent], description: "How much a joint bounces back off the ground. Defaults to 0%." },
+        Head {
+            name: "height",
+            arguments: &[Argument::Either(&[Argument::Length, Argument::Number])],
+            description: "Where the ground is, in meters if no unit is given. Defaults to 0.",
+        },
+    ],
+};
+
+pub static BUILD: Context = Context {
+    name: "build",
+    heads: &[
+        Head { name: "seed", arguments: &[Argument::OneOf(SeedType::NAMES)], description: "The brick everything grows from." },
+        Head { name: "scale", arguments: &[Argument::Percent], description: "The size of the seed brick relative to the fabric scale." },
+        Head { name: "vulcanize", arguments: &[Argument::OneOf(VulcanizeType::NAMES)], description: "Extra pulls added once growth is done." },
+        Head { name: "grow", arguments: GROW_ARGUMENTS, description: GROW_DESCRIPTION },
+        Head { name: "branch", arguments: &[Argument::Forms("branch")], description: "Grows from several faces of the seed." },
+        Head { name: "merge", arguments: &[Argument::Forms("growth")], description: "Grafts growth onto the growth of the extended fabric." },
+    ],
+};
+
+pub static GROWTH: Context = Context {
+    name: "growth",
+    heads: &[
+        Head { name: "grow", arguments: GROW_ARGUMENTS, description: GROW_DESCRIPTION },
+        Head { name: "branch", arguments: &[Argument::Forms("branch")], description: "Grows from several faces at once." },
+    ],
+};
+
+pub static BRANCH: Context = Context {
+    name: "branch",
+    heads: &[
+        Head { name: "grow", arguments: GROW_ARGUMENTS, description: "Grows from a face not used by any other grow in the branch." },
+    ],
+};
+
+pub static GROW: Context = Context {
+    name: "grow",
+    heads: &[
+        Head { name: "mark", arguments: &[Argument::Face, Argument::Name], description: "Names a face of the last brick for shaping." },
+        Head { name: "branch", arguments: &[Argument::Forms("branch")], description: "Grows on from several faces of the last brick, at most once per grow." },
+    ],
+};
+
+/// Forms that can stand in for any value or form inside a fabric, resolved while
+/// interpreting from the fabric's random seed.
+pub static RANDOM: Context = Context {
+    name: "random",
+    heads: &[
+        Head { name: "choose", arguments: &[Argument::Term], description: "One of any number of alternatives." },
+        Head { name: "random-int", arguments: &[Argument::Integer, Argument::Integer], description: "A whole number from the first to the second inclusive." },
+        Head { name: "maybe", arguments: &[Argument::Percent, Argument::Term], description: "The term with the given chance, and otherwise nothing." },
+    ],
+};
+
+pub static CONTEXTS: &[&Context] = &[&FABRIC, &PARAM, &SURFACE, &BUILD, &GROWTH, &BRANCH, &GROW, &RANDOM];
+
+/// The whole language as tenscript: every context with its heads, argument types and
+/// allowed atoms, and the `features` context from the feature registry.
+pub fn to_sexp() -> Sexp {
+    let mut contexts: Vec<_> = CONTEXTS.iter().map(|context| context.to_sexp()).collect();
+    contexts.push(call("features", features::FEATURES.iter().map(|spec| {
+        let value_type = spec.value_type();
+        let (min, max) = (value_type.to_sexp(*spec.range().start()), value_type.to_sexp(*spec.range().end()));
+        call(spec.name(), vec![
+            atom(&value_type.to_string()),
+            call("range", vec![min, max]),
+            call("default", vec![value_type.to_sexp(spec.default_value())]),
+            Sexp::String(spec.description().to_string()),
+        ])
+    }).collect()));
+    call("schema", contexts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::interpret_library;
+    use crate::interpreter::schema::{Argument, Head, CONTEXTS};
+    use crate::interpreter::unparse::{atom, call, ident};
+    use crate::quantity::Unit;
+    use crate::sexp::Sexp;
+
+    /// Arguments of the kinds the schema gives for the head, and one form for each
+    /// `Forms` argument of a context the schema describes.
+    fn example(head: &Head) -> Sexp {
+        let mut terms = Vec::new();
+        for argument in head.arguments() {
+            terms.extend(example_argument(*argument));
+        }
+        call(head.name(), terms)
+    }
+
+    fn example_argument(argument: Argument) -> Option<Sexp> {
+        Some(match argument {
+            Argument::String => Sexp::String("Base".to_string()),
+            Argument::Integer | Argument::Number | Argument::Value | Argument::Term => Sexp::Integer(1),
+            Argument::Percent => Sexp::Percent(100.0),
+            Argument::Length => Sexp::Quantity(1.0, Unit::Meters),
+            Argument::Name => atom("x"),
+            Argument::Identifier => ident("x"),
+            Argument::Face => atom("A+"),
+            Argument::OneOf(names) => atom(names[0]),
+            Argument::Either(arguments) => return example_argument(arguments[0]),
+            Argument::Forms(context) => example(CONTEXTS.iter().find(|c| c.name() == context)?.heads().first()?),
+        })
+    }
+
+    /// Every head in the schema interprets without errors in its context, given arguments
+    /// of the kinds the schema gives for it.
+    #[test]
+    fn schema_heads_interpret() {
+        for context in CONTEXTS {
+            for head in context.heads() {
+                let form = example(head);
+                let fabric = match context.name() {
+                    "fabric" if head.name() == "name" => format!("{form}"),
+                    "fabric" => format!("(name \"Test\") {form}"),
+                    "param" => format!("(name \"Test\") (param x 1 {form})"),
+                    "surface" => format!("(name \"Test\") (surface {form})"),
+                    "build" | "growth" => format!("(name \"Test\") (extends \"Base\") (build {form})"),
+                    "branch" => format!("(name \"Test\") (build (branch {form}))"),
+                    "grow" => format!("(name \"Test\") (build (grow A+ 1 {form}))"),
+                    "random" => format!("(name \"Test\") (build (grow A+ {form}))"),
+                    name => panic!("no example fabric for the {name} context"),
+                };
+                // The name example names the fabric after the base, so it stands alone.
+                let source = match head.name() {
+                    "name" => format!("(fabric {fabric})"),
+                    _ => format!("(fabric (name \"Base\")) (fabric {fabric})"),
+                };
+                if let Err(error) = interpret_library(&source) {
+                    panic!("{} in the {} context does not interpret: {error} in {source}", head.name(), context.name());
+                }
+            }
+        }
+    }
+}
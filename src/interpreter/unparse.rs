@@ -0,0 +1,198 @@
+use crate::interpreter::{BuildPhase, FabricPlan, FaceName, Mark, SeedType, Surface, SurfaceCharacter, TenscriptNode, VulcanizeType};
+use crate::quantity::Unit;
+use crate::sexp;
+use crate::sexp::Sexp;
+
+/// Canonical tenscript for a plan, which interprets back into an equal plan. Tenscript has
+/// no escapes, so this holds because a plan's name and marks are checked when it is
+/// interpreted or built to be writable as they are.
+pub fn unparse(plan: &FabricPlan) -> String {
+    sexp::pretty(&plan.to_sexp())
+}
+
+impl FabricPlan {
+    /// The canonical form of this plan: properties in a fixed order, features in registry
+    /// order, and only what is actually set.
+    pub fn to_sexp(&self) -> Sexp {
+        let FabricPlan { name, scale, surface, features, build_phase } = self;
+        let mut terms = vec![ident("fabric")];
+        if let Some(name) = name {
+            terms.push(call("name", vec![Sexp::String(name.clone())]));
+        }
+        if let Some(scale) = scale {
+            terms.push(call("scale", vec![Sexp::Quantity(scale.meters(), Unit::Meters)]));
+        }
+        if let Some(surface) = surface {
+            terms.push(surface.to_sexp());
+        }
+        if features.values().next().is_some() {
+            terms.push(features.to_sexp());
+        }
+        if let Some(build) = build_phase.to_sexp() {
+            terms.push(build);
+        }
+        Sexp::List(terms, None)
+    }
+}
+
+impl BuildPhase {
+    /// The `(build ..)` form, or nothing if the build phase is empty.
+    pub fn to_sexp(&self) -> Option<Sexp> {
+        let BuildPhase { seed, scale, vulcanize, growth } = self;
+        let mut terms = vec![ident("build")];
+        if let Some(seed) = seed {
+            terms.push(call("seed", vec![seed.to_sexp()]));
+        }
+        if let Some(scale) = scale {
+            terms.push(call("scale", vec![Sexp::Percent(scale.value())]));
+        }
+        if let Some(growth) = growth {
+            terms.push(growth.to_sexp());
+        }
+        if let Some(vulcanize) = vulcanize {
+            terms.push(call("vulcanize", vec![vulcanize.to_sexp()]));
+        }
+        if terms.len() == 1 {
+            return None;
+        }
+        Some(Sexp::List(terms, None))
+    }
+}
+
+impl TenscriptNode {
+    pub fn to_sexp(&self) -> Sexp {
+        match self {
+            TenscriptNode::Grow { face, forward_count, branch, marks } => {
+                let mut terms = vec![face.to_sexp(), Sexp::Integer(*forward_count as i64)];
+                terms.extend(marks.iter().map(Mark::to_sexp));
+                if let Some(branch) = branch {
+                    terms.push(branch.to_sexp());
+                }
+                call("grow", terms)
+            }
+            TenscriptNode::Branch { subtrees } => {
+                call("branch", subtrees.iter().map(TenscriptNode::to_sexp).collect())
+            }
+        }
+    }
+}
+
+impl Mark {
+    pub fn to_sexp(&self) -> Sexp {
+        call("mark", vec![self.face.to_sexp(), Sexp::Atom(self.name.clone())])
+    }
+}
+
+impl FaceName {
+    pub fn to_sexp(self) -> Sexp {
+        Sexp::Atom(self.to_string())
+    }
+}
+
+impl Surface {
+    /// The `(surface ..)` form.
+    pub fn to_sexp(self) -> Sexp {
+        match self {
+            Surface::Character(character) => call("surface", vec![character.to_sexp()]),
+            Surface::Tuned { friction, restitution, height } => call("surface", vec![
+                call("friction", vec![Sexp::Percent(friction.value())]),
+                call("restitution", vec![Sexp::Percent(restitution.value())]),
+                call("height", vec![Sexp::Quantity(height.meters(), Unit::Meters)]),
+            ]),
+        }
+    }
+}
+
+impl SurfaceCharacter {
+    pub fn to_sexp(self) -> Sexp {
+        atom(self.name())
+    }
+}
+
+impl SeedType {
+    pub fn to_sexp(self) -> Sexp {
+        atom(self.name())
+    }
+}
+
+impl VulcanizeType {
+    pub fn to_sexp(self) -> Sexp {
+        atom(self.name())
+    }
+}
+
+pub(super) fn ident(name: &str) -> Sexp {
+    Sexp::Ident(name.to_string())
+}
+
+pub(super) fn atom(name: &str) -> Sexp {
+    Sexp::Atom(name.to_string())
+}
+
+pub(super) fn call(head: &str, mut tail: Vec<Sexp>) -> Sexp {
+    tail.insert(0, ident(head));
+    Sexp::List(tail, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{interpret, interpret_library, FabricPlan, FaceName};
+    use crate::interpreter::unparse::unparse;
+
+    const CORPUS: &str = r#"
+        (fabric
+         (name "Everything")
+         (scale 30cm)
+         (surface :sticky)
+         (features
+          (iterations-per-frame 250)
+          (visual-strain 0.07%)
+          (gravity 1.25%)
+          (pretenst-factor 120%)
+          (stiffness-factor 33.3%)
+          (push-over-pull 1%)
+          (drag 0%)
+          (shaping-pretenst-factor 7%)
+          (shaping-drag 999.99%)
+          (shaping-stiffness-factor 100%)
+          (antigravity 0.1%)
+          (interval-countdown 2s)
+          (pretensing-countdown 1234ms))
+         (build
+          (seed :left-right)
+          (scale 7%)
+          (branch
+           (grow A+ 0)
+           (grow B+ 1 (mark C- :a) (mark D- :a))
+           (grow C+ 2 (branch (grow D+ 3 (branch (grow A- 4 (mark B- :deep))))))
+           (grow D+ 5))
+          (vulcanize :snelson)))
+        (fabric (name "Empty"))
+        (fabric (name "Percent Scale") (scale 150%) (surface :frozen) (build (seed :right) (vulcanize :bowtie)))
+        (fabric (name "Tuned Floor") (surface (friction 40%) (restitution 20%) (height 0)))
+        (fabric (name "Raised Floor") (surface (height 25cm)))
+        (fabric (name "Sunken Floor") (surface (friction 5%) (height -1.5)))
+        (fabric (name "Single Grow") (surface :bouncy) (build (seed :left) (grow B- 3 (mark A+ :x))))
+    "#;
+
+    #[test]
+    fn round_trip_corpus() {
+        let mut plans: Vec<_> = interpret_library(CORPUS).unwrap().into_values().collect();
+        plans.extend(interpret_library(include_str!("../../library.ss")).unwrap().into_values());
+        plans.push(interpret(include_str!("../../example.ss")).unwrap());
+        plans.push(FabricPlan::builder()
+            .name("Knee's 2nd (left) :x")
+            .grow(FaceName::Aplus, 1, |g| g.mark(FaceName::Aplus, "a-1+"))
+            .build()
+            .unwrap());
+        assert!(FabricPlan::builder().name("q\"uote").build().is_err());
+        assert!(FabricPlan::builder().grow(FaceName::Aplus, 1, |g| g.mark(FaceName::Aplus, "1x")).build().is_err());
+        for plan in plans {
+            let source = unparse(&plan);
+            let reinterpreted = interpret(&source)
+                .unwrap_or_else(|error| panic!("{source} does not interpret: {error}"));
+            assert_eq!(reinterpreted, plan, "round trip through {source}");
+            assert_eq!(unparse(&reinterpreted), source);
+        }
+    }
+}
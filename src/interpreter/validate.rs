@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use crate::interpreter::{FabricPlan, FaceName, Grow, NodePath, SourceKey, SourceMap};
+use crate::scanner::Location;
+
+/// Ways a plan can be well formed grow by grow and still not make sense as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViolationKind {
+    /// A mark on a face that the grow's own branch grows from, so the face is gone.
+    MarkOnGrowthFace { face: FaceName, name: String },
+    /// A mark on the face the grow's last brick is attached by, so the face is gone.
+    MarkOnAttachingFace { face: FaceName, name: String },
+    /// Two marks with the same name on one grow.
+    MarkNameCollision { name: String },
+    /// A build property that only makes sense once something has been grown.
+    NoGrowth { property: &'static str },
+}
+
+impl Display for ViolationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViolationKind::MarkOnGrowthFace { face, name } =>
+                write!(f, "mark :{name} is on {face}, which the branch grows from"),
+            ViolationKind::MarkOnAttachingFace { face, name } =>
+                write!(f, "mark :{name} is on {face}, which the last brick is attached by"),
+            ViolationKind::MarkNameCollision { name } =>
+                write!(f, "mark :{name} is placed more than once"),
+            ViolationKind::NoGrowth { property } =>
+                write!(f, "{property} has no growth to act on"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Violation {
+    kind: ViolationKind,
+    path: NodePath,
+    location: Option<Location>,
+}
+
+impl Violation {
+    pub fn kind(&self) -> &ViolationKind {
+        &self.kind
+    }
+
+    /// The grow the violation is in, or the empty path for the build phase as a whole.
+    pub fn path(&self) -> &NodePath {
+        &self.path
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Violation { kind, path, location } = self;
+        match location {
+            Some(location) => write!(f, "invalid at {location} in {path}: {kind}"),
+            None => write!(f, "invalid in {path}: {kind}"),
+        }
+    }
+}
+
+/// Checks the rules that span more than one grow, which interpreting a grow on its own
+/// cannot see. The seed face needs no check here, because the interpreter only takes the
+/// eight named faces to grow from or mark.
+///
+/// A vulcanize with no growth is both a violation and a finding of the
+/// [vulcanize-without-growth](crate::interpreter::lint::Lint::VulcanizeWithoutGrowth) lint,
+/// so disabling the lint leaves the violation in place.
+///
+/// ```
+/// use tenscript::interpreter::interpret_with_diagnostics;
+/// use tenscript::interpreter::lint::{Lint, LintSet};
+/// use tenscript::interpreter::validate::ViolationKind;
+///
+/// let interpretation = interpret_with_diagnostics("(fabric (build (seed :left) (vulcanize :bowtie)))").unwrap();
+/// let violations = interpretation.validate();
+/// assert_eq!(violations[0].kind(), &ViolationKind::NoGrowth { property: "vulcanize" });
+/// let findings = interpretation.lint(&LintSet::all());
+/// assert_eq!(findings[0].lint(), Lint::VulcanizeWithoutGrowth);
+/// assert!(interpretation.lint(&LintSet::all().disable(Lint::VulcanizeWithoutGrowth)).is_empty());
+/// ```
+pub fn validate_plan(plan: &FabricPlan, source_map: &SourceMap) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut violation = |kind, path: &NodePath, key| {
+        let location = source_map.location(&key);
+        violations.push(Violation { kind, path: path.clone(), location });
+    };
+    let build_phase = &plan.build_phase;
+    if build_phase.growth.is_none() {
+        let root = NodePath::default();
+        if build_phase.vulcanize.is_some() {
+            violation(ViolationKind::NoGrowth { property: "vulcanize" }, &root, SourceKey::Build("vulcanize"));
+        }
+        if build_phase.scale.is_some() {
+            violation(ViolationKind::NoGrowth { property: "scale" }, &root, SourceKey::Build("scale"));
+        }
+    }
+    for grow in build_phase.growth.iter().flat_map(|growth| growth.grows()) {
+        let path = grow.path();
+        let branch_faces = branch_faces(&grow);
+        let mut names = HashSet::new();
+        for (index, mark) in grow.marks().iter().enumerate() {
+            let key = || SourceKey::Mark(path.clone(), index);
+            if grow.forward_count() > 0 && mark.face == grow.face().opposite() {
+                violation(ViolationKind::MarkOnAttachingFace { face: mark.face, name: mark.name.clone() }, path, key());
+            } else if branch_faces.contains(&mark.face) {
+                violation(ViolationKind::MarkOnGrowthFace { face: mark.face, name: mark.name.clone() }, path, key());
+            }
+            if !names.insert(&mark.name) {
+                violation(ViolationKind::MarkNameCollision { name: mark.name.clone() }, path, key());
+            }
+        }
+    }
+    violations
+}
+
+/// The faces of the last brick that the grow's branch grows from.
+fn branch_faces(grow: &Grow) -> HashSet<FaceName> {
+    grow.branch().into_iter()
+        .flat_map(|branch| branch.grows())
+        .filter(|child| child.path().faces().len() == 1)
+        .map(|child| child.face())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fabric::growth::grow;
+    use crate::interpreter::{interpret_with_diagnostics, FaceName};
+    use crate::interpreter::validate::ViolationKind;
+
+    /// Each violation in the source as its kind, node path and line.
+    fn violations(source: &str) -> Vec<(ViolationKind, String, usize)> {
+        interpret_with_diagnostics(source).unwrap()
+            .validate()
+            .into_iter()
+            .map(|violation| (violation.kind().clone(), violation.path().to_string(), violation.location().expect("located violation").line()))
+            .collect()
+    }
+
+    #[test]
+    fn mark_on_growth_face() {
+        let source = "(fabric (build (grow A+ 1\n (mark B+ :x)\n (branch (grow B+ 1 (mark C+ :y))))))";
+        let kind = ViolationKind::MarkOnGrowthFace { face: FaceName::Bplus, name: "x".to_string() };
+        assert_eq!(violations(source), [(kind, "grow A+".to_string(), 2)]);
+    }
+
+    #[test]
+    fn mark_on_attaching_face() {
+        let source = "(fabric (build (branch (grow A+ 2\n (mark A- :x)) (grow B+ 0 (mark B- :y)))))";
+        let kind = ViolationKind::MarkOnAttachingFace { face: FaceName::Aminus, name: "x".to_string() };
+        assert_eq!(violations(source), [(kind, "grow A+".to_string(), 2)]);
+        let plan = interpret_with_diagnostics(source).unwrap().plan().clone();
+        assert!(grow(&plan).is_err());
+    }
+
+    #[test]
+    fn mark_name_collision() {
+        let source = "(fabric (build (grow A+ 1 (branch (grow B+ 1 (mark C+ :x)\n (mark D+ :x))))))";
+        let kind = ViolationKind::MarkNameCollision { name: "x".to_string() };
+        assert_eq!(violations(source), [(kind, "grow A+ > grow B+".to_string(), 2)]);
+    }
+
+    #[test]
+    fn no_growth() {
+        let source = "(fabric (build (seed :left)\n (scale 50%)\n (vulcanize :snelson)))";
+        assert_eq!(violations(source), [
+            (ViolationKind::NoGrowth { property: "vulcanize" }, "growth".to_string(), 3),
+            (ViolationKind::NoGrowth { property: "scale" }, "growth".to_string(), 2),
+        ]);
+    }
+}
@@ -0,0 +1,24 @@
+//! Tenscript describes tensegrity fabrics as s-expressions. Source is scanned into tokens,
+//! parsed into a [Sexp](sexp::Sexp), and interpreted into a [FabricPlan]. The tensegrity
+//! itself is a [Fabric](fabric::Fabric).
+//!
+//! ```
+//! let plan = tenscript::interpret("(fabric (name \"Knee\") (build (seed :left) (grow A+ 3)))").unwrap();
+//! assert_eq!(plan.name(), Some("Knee"));
+//! let growth = plan.build_phase().growth().unwrap();
+//! assert_eq!(growth.grows().count(), 1);
+//! ```
+
+pub mod error;
+pub mod fabric;
+pub mod interpreter;
+pub mod quantity;
+pub mod scanner;
+pub mod sexp;
+mod suggest;
+
+pub use error::Error;
+pub use interpreter::{
+    BuildPhase, FabricPlan, FaceName, Features, Grow, Mark, NodePath, SeedType, Surface, SurfaceCharacter, TenscriptNode,
+    VulcanizeType, interpret, interpret_library, interpret_with_diagnostics,
+};
@@ -1,22 +1,103 @@
-#![feature(let_else)]
-
+use std::env;
 use std::error::Error;
 use std::fs;
 
-mod scanner;
-mod sexp;
-mod error;
-mod interpreter;
+use tenscript::{interpreter, scanner, sexp};
+use tenscript::interpreter::{features, schema};
+use tenscript::interpreter::Overrides;
+use tenscript::interpreter::lint::LintSet;
+use tenscript::interpreter::params::Value;
 
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let source = fs::read_to_string("example.ss")?;
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("features") {
+        args.next();
+        return list_features(args.next().as_deref(), args.next().as_deref());
+    }
+    if args.peek().map(String::as_str) == Some("schema") {
+        print!("{}", sexp::pretty(&schema::to_sexp()));
+        return Ok(());
+    }
+    if args.peek().map(String::as_str) == Some("params") {
+        args.next();
+        return list_params(&fs::read_to_string(args.next().unwrap_or_else(|| "example.ss".to_string()))?);
+    }
+    let mut overrides = Overrides::default();
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--param" => {
+                let assignment = args.next().unwrap_or_default();
+                let Some((name, value)) = assignment.split_once('=') else {
+                    return Err(format!("expected --param <name>=<value> but got {assignment:?}").into());
+                };
+                let Some(value) = Value::parse(value) else {
+                    return Err(format!("{value:?} is not a value for param {name}").into());
+                };
+                overrides = overrides.param(name, value);
+            }
+            "--random-seed" => {
+                let seed = args.next().unwrap_or_default();
+                let Ok(seed) = seed.parse() else {
+                    return Err(format!("expected --random-seed <non-negative integer> but got {seed:?}").into());
+                };
+                overrides = overrides.random_seed(seed);
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let mut positional = positional.into_iter();
+    let path = positional.next().unwrap_or_else(|| "example.ss".to_string());
+    let source = fs::read_to_string(path)?;
+    if let Some(name) = positional.next() {
+        let library = interpreter::interpret_library(&source)?;
+        let Some(fabric) = library.get(&name) else {
+            let names: Vec<_> = library.keys().collect();
+            return Err(format!("no fabric named {name:?} in library {names:?}").into());
+        };
+        println!("{fabric:#?}");
+        return Ok(());
+    }
     let tokens = scanner::scan(&source)?;
     // let just_tokens: Vec<_> = tokens.iter().map(|t| t.tok.clone()).collect();
     // println!("{just_tokens:?}");
     let sexp = sexp::parse_tokens(tokens)?;
     println!("{sexp}");
-    let fabric = interpreter::interpret_sexp(&sexp)?;
-    println!("{fabric:#?}");
+    let interpretation = interpreter::interpret_sexp_with_overrides_and_diagnostics(&sexp, &overrides);
+    for diagnostic in interpretation.diagnostics() {
+        eprintln!("{diagnostic}");
+    }
+    for violation in interpretation.validate() {
+        eprintln!("{violation}");
+    }
+    for finding in interpretation.lint(&LintSet::default()) {
+        eprintln!("{finding}");
+    }
+    println!("{:#?}", interpretation.plan());
+    Ok(())
+}
+
+fn list_params(source: &str) -> Result<(), Box<dyn Error>> {
+    for param in interpreter::interpret_with_diagnostics(source)?.params() {
+        println!("{param}");
+    }
+    Ok(())
+}
+
+fn list_features(option: Option<&str>, prefix: Option<&str>) -> Result<(), Box<dyn Error>> {
+    match option {
+        Some("--list") => {
+            for spec in features::FEATURES {
+                println!("{spec}");
+            }
+        }
+        Some("--complete") => {
+            for spec in features::complete(prefix.unwrap_or_default()) {
+                println!("{}", spec.name());
+            }
+        }
+        _ => return Err("usage: tenscript features --list | --complete <prefix>".into()),
+    }
     Ok(())
 }
@@ -0,0 +1,207 @@
+use std::fmt::{Display, Formatter};
+
+/// How many simulation iterations make up one second of simulated time.
+pub const ITERATIONS_PER_SECOND: f64 = 4000.0;
+
+/// A unit suffix on a number literal, such as the `cm` in `30cm`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Unit {
+    Meters,
+    Centimeters,
+    Millimeters,
+    Iterations,
+    Seconds,
+    Milliseconds,
+}
+
+impl Unit {
+    pub fn from_suffix(suffix: &str) -> Option<Unit> {
+        Some(match suffix {
+            "m" => Unit::Meters,
+            "cm" => Unit::Centimeters,
+            "mm" => Unit::Millimeters,
+            "it" => Unit::Iterations,
+            "s" => Unit::Seconds,
+            "ms" => Unit::Milliseconds,
+            _ => return None,
+        })
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Unit::Meters => "m",
+            Unit::Centimeters => "cm",
+            Unit::Millimeters => "mm",
+            Unit::Iterations => "it",
+            Unit::Seconds => "s",
+            Unit::Milliseconds => "ms",
+        }
+    }
+
+    /// The length a literal in this unit stands for, if this is a unit of length.
+    pub fn length(self, value: f64) -> Option<Length> {
+        match self {
+            Unit::Meters => Some(Length::from_meters(value)),
+            Unit::Centimeters => Some(Length::from_meters(value / 100.0)),
+            Unit::Millimeters => Some(Length::from_meters(value / 1000.0)),
+            _ => None,
+        }
+    }
+
+    /// The duration a literal in this unit stands for, if this is a unit of time.
+    pub fn duration(self, value: f64) -> Option<Duration> {
+        match self {
+            Unit::Iterations => Some(Duration::from_iterations(value)),
+            Unit::Seconds => Some(Duration::from_seconds(value)),
+            Unit::Milliseconds => Some(Duration::from_seconds(value / 1000.0)),
+            _ => None,
+        }
+    }
+}
+
+/// A proportion written as a percentage, such as `150%`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+pub struct Percent(f64);
+
+impl Percent {
+    pub fn new(percent: f64) -> Percent {
+        Percent(percent)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    pub fn ratio(self) -> Ratio {
+        Ratio(self.0 / 100.0)
+    }
+}
+
+impl Display for Percent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// A proportion as a plain multiplier, where `1.0` leaves things unchanged.
+#[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+pub struct Ratio(f64);
+
+impl Ratio {
+    pub fn new(ratio: f64) -> Ratio {
+        Ratio(ratio)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    pub fn percent(self) -> Percent {
+        Percent(self.0 * 100.0)
+    }
+}
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+pub struct Length {
+    meters: f64,
+}
+
+impl Length {
+    pub fn from_meters(meters: f64) -> Length {
+        Length { meters }
+    }
+
+    pub fn meters(self) -> f64 {
+        self.meters
+    }
+
+    pub fn scaled(self, ratio: Ratio) -> Length {
+        Length { meters: self.meters * ratio.value() }
+    }
+}
+
+impl Display for Length {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}m", self.meters)
+    }
+}
+
+/// Simulated time, counted in iterations. Seconds are converted at [ITERATIONS_PER_SECOND].
+#[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+pub struct Duration {
+    iterations: f64,
+}
+
+impl Duration {
+    pub fn from_iterations(iterations: f64) -> Duration {
+        Duration { iterations }
+    }
+
+    pub fn from_seconds(seconds: f64) -> Duration {
+        Duration { iterations: seconds * ITERATIONS_PER_SECOND }
+    }
+
+    pub fn iterations(self) -> f64 {
+        self.iterations
+    }
+
+    pub fn seconds(self) -> f64 {
+        self.iterations / ITERATIONS_PER_SECOND
+    }
+}
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}it", self.iterations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::interpreter::{interpret, ErrorKind};
+    use crate::quantity::{Duration, Length, Unit};
+    use crate::scanner;
+
+    #[test]
+    fn lengths() {
+        assert_eq!(Unit::Centimeters.length(30.0), Some(Length::from_meters(0.3)));
+        assert_eq!(Unit::Millimeters.length(250.0), Some(Length::from_meters(0.25)));
+        assert_eq!(Unit::Seconds.length(1.0), None);
+        assert_eq!(interpret("(fabric (scale 30cm))").unwrap().scale(), Some(Length::from_meters(0.3)));
+        assert_eq!(interpret("(fabric (scale 150%))").unwrap().scale(), Some(Length::from_meters(1.5)));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(Unit::Seconds.duration(2.0), Some(Duration::from_iterations(8000.0)));
+        assert_eq!(Unit::Milliseconds.duration(1234.0), Some(Duration::from_iterations(4936.0)));
+        assert_eq!(Unit::Meters.duration(1.0), None);
+        let plan = interpret("(fabric (features (interval-countdown 2s) (pretensing-countdown 1234ms)))").unwrap();
+        assert_eq!(plan.features().interval_countdown(), Some(Duration::from_iterations(8000.0)));
+        assert_eq!(plan.features().pretensing_countdown(), Some(Duration::from_iterations(4936.0)));
+    }
+
+    #[test]
+    fn unknown_unit() {
+        let Err(Error::ScanError(error)) = scanner::scan("3xy") else {
+            panic!("3xy should not scan");
+        };
+        assert!(matches!(error.kind(), scanner::ErrorKind::UnknownUnit { suffix } if suffix == "xy"), "{error}");
+    }
+
+    /// Countdowns used to be written as percentages, which is now a type error.
+    #[test]
+    fn countdown_as_percent() {
+        let Err(Error::InterpretError(error)) = interpret("(fabric (features (pretensing-countdown 100%)))") else {
+            panic!("a percent countdown should not interpret");
+        };
+        assert!(matches!(error.kind(), ErrorKind::TypeError { expected: "duration", .. }), "{error}");
+    }
+}
@@ -2,8 +2,9 @@ use std::fmt::{Display, Formatter};
 use std::num::{ParseFloatError, ParseIntError};
 use std::str::FromStr;
 use crate::error;
-use crate::scanner::ErrorKind::{FloatParseFailed, IllegalChar, IntParseFailed};
-use crate::scanner::Token::{Atom, EOF, Ident, Integer, Float, Paren, Percent, String as StringLit};
+use crate::quantity::Unit;
+use crate::scanner::ErrorKind::{FloatParseFailed, IllegalChar, IntParseFailed, UnknownUnit, UnterminatedString};
+use crate::scanner::Token::{Atom, Eof, Ident, Integer, Float, Paren, Percent, Quantity, String as StringLit};
 
 #[derive(Debug, Clone)]
 pub enum Token {
@@ -14,19 +15,36 @@ pub enum Token {
     Integer(i64),
     Float(f64),
     Percent(f64),
-    EOF,
+    Quantity(f64, Unit),
+    Eof,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Location {
     line: usize,
     col: usize,
 }
 
+impl Default for Location {
+    fn default() -> Self {
+        Self { line: 1, col: 1 }
+    }
+}
+
+impl Location {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn col(&self) -> usize {
+        self.col
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ScannedToken {
     pub(crate) tok: Token,
-    loc: Location,
+    pub(crate) loc: Location,
 }
 
 impl Display for Location {
@@ -42,6 +60,16 @@ pub struct ScanError {
     loc: Location,
 }
 
+impl ScanError {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> Location {
+        self.loc
+    }
+}
+
 impl Display for ScanError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let ScanError { kind, loc } = self;
@@ -57,6 +85,28 @@ pub enum ErrorKind {
     IllegalChar { ch: char },
     IntParseFailed { err: ParseIntError },
     FloatParseFailed { err: ParseFloatError },
+    UnknownUnit { suffix: String },
+    UnterminatedString,
+}
+
+/// Whether the name can be written as an atom or identifier: a letter followed by letters,
+/// digits, `-` and `+`.
+pub(crate) fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(starts_name) && chars.all(continues_name)
+}
+
+/// Whether the text can be written between quotes, which it can unless it has a quote in it.
+pub(crate) fn is_string(text: &str) -> bool {
+    !text.contains('"')
+}
+
+fn starts_name(ch: char) -> bool {
+    ch.is_ascii_alphabetic()
+}
+
+fn continues_name(ch: char) -> bool {
+    matches!(ch, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '+')
 }
 
 pub fn scan(source: &str) -> Result<Vec<ScannedToken>, error::Error> {
@@ -68,6 +118,7 @@ struct Scanner {
     tokens: Vec<ScannedToken>,
     index: usize,
     start: usize,
+    start_loc: Location,
     loc: Location,
 }
 
@@ -78,6 +129,7 @@ impl Scanner {
             tokens: Default::default(),
             start: 0,
             index: 0,
+            start_loc: Default::default(),
             loc: Default::default(),
         }
     }
@@ -85,10 +137,12 @@ impl Scanner {
     pub fn scan(mut self) -> Result<Vec<ScannedToken>, ScanError> {
         while !self.at_end() {
             self.start = self.index;
+            self.start_loc = self.loc;
             self.scan_token()
-                .map_err(|kind| ScanError { kind, loc: self.loc.clone() })?;
+                .map_err(|kind| ScanError { kind, loc: self.loc })?;
         }
-        self.add(EOF);
+        self.start_loc = self.loc;
+        self.add(Eof);
         Ok(self.tokens)
     }
 
@@ -99,10 +153,10 @@ impl Scanner {
             'a'..='z' => self.ident(),
             'A'..='Z' => self.atom(false),
             ':' => self.atom(true),
-            '"' => self.string(),
+            '"' => self.string()?,
             '\n' => {
                 self.loc.line += 1;
-                self.loc.col = 0;
+                self.loc.col = 1;
                 self.increment();
             }
             ' ' | '\t' => {
@@ -123,7 +177,7 @@ impl Scanner {
     }
 
     fn current(&self) -> char {
-        self.chars[self.index]
+        self.chars.get(self.index).copied().unwrap_or('\0')
     }
 
     fn increment(&mut self) {
@@ -135,7 +189,7 @@ impl Scanner {
     fn add(&mut self, tok: Token) {
         self.tokens.push(ScannedToken {
             tok,
-            loc: self.loc.clone(),
+            loc: self.start_loc,
         })
     }
 
@@ -144,11 +198,11 @@ impl Scanner {
     }
 
     fn consume_ident_chars(&mut self) {
-        let ('a'..='z' | 'A'..='Z') = self.current() else {
+        if !starts_name(self.current()) {
             return;
-        };
+        }
         self.increment();
-        while let 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '+' = self.current() {
+        while continues_name(self.current()) {
             self.increment();
         }
     }
@@ -165,40 +219,46 @@ impl Scanner {
             }
             self.increment();
         }
-        if let '.' = self.current() {
+        let is_float = self.current() == '.';
+        if is_float {
             num_string.push('.');
             self.increment();
             while let ch @ '0'..='9' = self.current() {
                 num_string.push(ch);
                 self.increment();
-                let mut value = f64::from_str(&num_string)
-                    .map_err(|err| FloatParseFailed { err })?;
-                if negative {
-                    value = -value;
-                }
-                match self.current() {
-                    '%' => {
-                        self.increment();
-                        self.add(Percent(value));
-                    }
-                    _ => self.add(Float(value)),
-                };
             }
-        } else {
-            let mut value = i64::from_str(&num_string)
-                .map_err(|err| IntParseFailed { err })?;
-            
-            if negative {
-                value = -value;
+        }
+        let mut value = f64::from_str(&num_string)
+            .map_err(|err| FloatParseFailed { err })?;
+        if negative {
+            value = -value;
+        }
+        match self.current() {
+            '%' => {
+                self.increment();
+                self.add(Percent(value));
             }
-            match self.current() {
-                '%' => {
+            'a'..='z' => {
+                let suffix_start = self.index;
+                while let 'a'..='z' = self.current() {
                     self.increment();
-                    self.add(Percent(value as f64));
                 }
-                _ => self.add(Integer(value)),
-            };
-        }
+                let suffix: String = self.chars[suffix_start..self.index].iter().collect();
+                let Some(unit) = Unit::from_suffix(&suffix) else {
+                    return Err(UnknownUnit { suffix });
+                };
+                self.add(Quantity(value, unit));
+            }
+            _ if is_float => self.add(Float(value)),
+            _ => {
+                let mut value = i64::from_str(&num_string)
+                    .map_err(|err| IntParseFailed { err })?;
+                if negative {
+                    value = -value;
+                }
+                self.add(Integer(value));
+            }
+        };
 
         Ok(())
     }
@@ -220,9 +280,12 @@ impl Scanner {
         let name = self.lexeme();
         self.add(Ident(name));
     }
-    fn string(&mut self) {
+    fn string(&mut self) -> Result<(), ErrorKind> {
         self.increment();
         while self.current() != '"' {
+            if self.at_end() {
+                return Err(UnterminatedString);
+            }
             self.increment();
         }
         self.increment();
@@ -230,5 +293,6 @@ impl Scanner {
         string.remove(0);
         string.remove(string.len() - 1);
         self.add(StringLit(string));
+        Ok(())
     }
 }
\ No newline at end of file
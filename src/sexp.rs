@@ -1,20 +1,22 @@
 use std::fmt::{Debug, Display, Formatter};
 use crate::error::Error;
+use crate::quantity::Unit;
 use crate::scanner;
-use crate::scanner::{ScannedToken, Token};
-use crate::scanner::Token::{Atom, Float, Ident, Integer, Paren, Percent, EOF};
+use crate::scanner::{Location, ScannedToken, Token};
+use crate::scanner::Token::{Atom, Float, Ident, Integer, Paren, Percent, Quantity, Eof};
 use crate::sexp::ErrorKind::{ConsumeFailed, MatchExhausted};
 
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum Sexp {
-    List(Vec<Sexp>),
+    List(Vec<Sexp>, Option<Location>),
     Ident(String),
     Atom(String),
     String(String),
     Integer(i64),
     Float(f64),
     Percent(f64),
+    Quantity(f64, Unit),
 }
 
 impl Debug for Sexp {
@@ -24,10 +26,13 @@ impl Debug for Sexp {
 }
 
 
+/// Writes the sexp as tenscript. Strings and names are written as they are, because
+/// tenscript has no escapes: a string with a quote in it or a name the scanner would split
+/// does not read back the same.
 impl Display for Sexp {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Sexp::List(terms) => {
+            Sexp::List(terms, _) => {
                 f.write_str("(")?;
                 for (i, term) in terms.iter().enumerate() {
                     Display::fmt(term, f)?;
@@ -42,18 +47,76 @@ impl Display for Sexp {
             Sexp::Atom(value) => write!(f, ":{value}"),
             Sexp::String(value) => write!(f, "\"{value}\""),
             Sexp::Percent(value) => write!(f, "{value}%"),
+            Sexp::Quantity(value, unit) => write!(f, "{value}{}", unit.suffix()),
+            Sexp::Float(value) if value.fract() == 0.0 => write!(f, "{value:.1}"),
             Sexp::Float(value) => write!(f, "{value}"),
             Sexp::Integer(value) => write!(f, "{value}"),
         }
     }
 }
 
+impl Sexp {
+    /// Where a list was opened in the source, if it was parsed rather than constructed.
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            Sexp::List(_, location) => *location,
+            _ => None,
+        }
+    }
+}
+
+/// Lays out a sexp the way tenscript is written by hand: the leading plain terms of a list
+/// share its line, and each nested list goes on a line of its own, one space further in.
+pub fn pretty(sexp: &Sexp) -> String {
+    let mut out = String::new();
+    write_pretty(sexp, 0, &mut out);
+    out.push('\n');
+    out
+}
+
+fn write_pretty(sexp: &Sexp, indent: usize, out: &mut String) {
+    let Sexp::List(terms, _) = sexp else {
+        match sexp {
+            Sexp::Atom(name) if name.starts_with(|ch: char| ch.is_ascii_uppercase()) => out.push_str(name),
+            _ => out.push_str(&sexp.to_string()),
+        }
+        return;
+    };
+    let first_list = terms.iter()
+        .position(|term| matches!(term, Sexp::List(..)))
+        .unwrap_or(terms.len());
+    let (inline, nested) = terms.split_at(first_list);
+    out.push('(');
+    for (i, term) in inline.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        write_pretty(term, indent + 1, out);
+    }
+    for term in nested {
+        out.push('\n');
+        out.push_str(&" ".repeat(indent + 1));
+        write_pretty(term, indent + 1, out);
+    }
+    out.push(')');
+}
+
 #[derive(Debug, Clone)]
 pub struct ParseError {
     kind: ErrorKind,
     token: ScannedToken,
 }
 
+impl ParseError {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> Location {
+        self.token.loc
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ErrorKind {
     MatchExhausted,
@@ -69,6 +132,11 @@ pub fn parse_tokens(tokens: Vec<ScannedToken>) -> Result<Sexp, Error> {
     Parser::new(tokens).parse().map_err(Error::SexpParseError)
 }
 
+pub fn parse_all(source: &str) -> Result<Vec<Sexp>, Error> {
+    let tokens = scanner::scan(source)?;
+    Parser::new(tokens).parse_all().map_err(Error::SexpParseError)
+}
+
 struct Parser {
     tokens: Vec<ScannedToken>,
     index: usize,
@@ -88,6 +156,16 @@ impl Parser {
             .map_err(|kind| ParseError { kind, token: self.current_scanned().clone() })
     }
 
+    pub fn parse_all(mut self) -> Result<Vec<Sexp>, ParseError> {
+        let mut sexps = Vec::new();
+        while !matches!(self.current(), Eof) {
+            let sexp = self.sexp()
+                .map_err(|kind| ParseError { kind, token: self.current_scanned().clone() })?;
+            sexps.push(sexp);
+        }
+        Ok(sexps)
+    }
+
     fn current_scanned(&self) -> &ScannedToken {
         &self.tokens[self.index]
     }
@@ -103,11 +181,11 @@ impl Parser {
     }
 
     fn sexp(&mut self) -> Result<Sexp, ErrorKind> {
-        let token = self.current().clone();
+        let ScannedToken { tok: token, loc } = self.current_scanned().clone();
         self.increment();
         match token {
             Paren('(') =>
-                self.list(),
+                self.list(loc),
             Ident(name) =>
                 Ok(Sexp::Ident(name)),
             Float(value) =>
@@ -116,6 +194,8 @@ impl Parser {
                 Ok(Sexp::Integer(value)),
             Percent(value) =>
                 Ok(Sexp::Percent(value)),
+            Quantity(value, unit) =>
+                Ok(Sexp::Quantity(value, unit)),
             Atom(value) =>
                 Ok(Sexp::Atom(value)),
             Token::String(value) =>
@@ -124,9 +204,9 @@ impl Parser {
         }
     }
 
-    fn list(&mut self) -> Result<Sexp, ErrorKind> {
+    fn list(&mut self, location: Location) -> Result<Sexp, ErrorKind> {
         let mut terms = Vec::new();
-        while !matches!(self.current(), Paren(')') | EOF) {
+        while !matches!(self.current(), Paren(')') | Eof) {
             let term = self.sexp()?;
             terms.push(term);
         }
@@ -134,6 +214,6 @@ impl Parser {
             return Err(ConsumeFailed { expected: "right paren" });
         };
         self.increment();
-        Ok(Sexp::List(terms))
+        Ok(Sexp::List(terms, Some(location)))
     }
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+/// Picks the candidate closest to a misspelled name, if any is close enough to be a likely typo.
+pub fn did_you_mean<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let limit = (name.chars().count() / 3).max(1);
+    candidates.iter()
+        .map(|&candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Optimal string alignment distance: single character insertions, deletions and
+/// substitutions, plus transpositions of adjacent characters, each count as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let deletion = distances[i - 1][j] + 1;
+            let insertion = distances[i][j - 1] + 1;
+            let mut distance = substitution.min(deletion).min(insertion);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::suggest::did_you_mean;
+
+    const HEADS: &[&str] = &["seed", "scale", "vulcanize", "grow", "branch", "merge"];
+
+    #[test]
+    fn close_matches() {
+        assert_eq!(did_you_mean("sede", HEADS), Some("seed"));
+        assert_eq!(did_you_mean("vulcanise", HEADS), Some("vulcanize"));
+        assert_eq!(did_you_mean("Branch", HEADS), Some("branch"));
+        assert_eq!(did_you_mean("gorw", HEADS), Some("grow"));
+    }
+
+    #[test]
+    fn no_match_when_too_far() {
+        assert_eq!(did_you_mean("shape", HEADS), None);
+        assert_eq!(did_you_mean("x", HEADS), None);
+        assert_eq!(did_you_mean("seed", &[]), None);
+    }
+
+    #[test]
+    fn closest_match_wins() {
+        assert_eq!(did_you_mean("scald", HEADS), Some("scale"));
+        assert_eq!(did_you_mean("A-", &["A+", "A-", "B-"]), Some("A-"));
+    }
+}